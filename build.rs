@@ -0,0 +1,56 @@
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// one `#[test]` per part of every `inputs/dayNN/<name>.txt`, included by src/fixtures.rs; the
+// empty placeholders `scaffold` leaves have no answers yet and are skipped
+fn main() {
+    println!("cargo:rerun-if-changed=inputs");
+
+    let mut fixtures = vec![];
+    for entry in fs::read_dir("inputs").into_iter().flatten().flatten() {
+        let dir = entry.path();
+        let Some(day) = day_number(&dir) else {
+            continue;
+        };
+
+        for file in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = file.path();
+            let placeholder = fs::metadata(&path).is_ok_and(|metadata| metadata.len() == 0);
+            if path.extension().is_some_and(|ext| ext == "txt") && !placeholder {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    fixtures.push((day, name.to_owned()));
+                }
+            }
+        }
+    }
+    fixtures.sort();
+
+    let mut tests = String::new();
+    for (day, name) in fixtures {
+        for (part, variant) in [(1, "One"), (2, "Two")] {
+            writeln!(
+                tests,
+                "#[test]\nfn day{day:02}_{}_part{part}() -> Result<()> {{\n    check({day}, {name:?}, Part::{variant})\n}}\n",
+                identifier(&name),
+            )
+            .unwrap();
+        }
+    }
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("fixtures.rs");
+    fs::write(out, tests).unwrap();
+}
+
+fn day_number(dir: &Path) -> Option<u8> {
+    dir.file_name()?.to_str()?.strip_prefix("day")?.parse().ok()
+}
+
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
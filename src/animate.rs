@@ -0,0 +1,335 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use eyre::{bail, Result};
+
+use crate::{
+    input::Source,
+    registry::Day,
+    simulation::Simulation,
+    solution::Part,
+};
+
+pub const DEFAULT_FPS: u32 = 20;
+const MAX_FPS: u32 = 1 << 14;
+// faster than this, several steps go into each drawn frame
+const DRAW_FPS: u32 = 50;
+
+const HELP: &str = "space pause, n step, +/- speed, <frame>g jump, q quit";
+
+/// How the animation starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub fps: u32,
+    pub frame: usize,
+    pub paused: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { fps: DEFAULT_FPS, frame: 0, paused: false }
+    }
+}
+
+// what the keys pressed so far ask for
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Controls {
+    fps: u32,
+    paused: bool,
+    steps: usize,
+    jump: Option<usize>,
+    digits: Option<usize>,
+    quit: bool,
+}
+
+impl Controls {
+    fn new(options: Options) -> Self {
+        Controls {
+            fps: options.fps.clamp(1, MAX_FPS),
+            paused: options.paused,
+            steps: 0,
+            jump: (options.frame > 0).then_some(options.frame),
+            digits: None,
+            quit: false,
+        }
+    }
+
+    fn press(&mut self, key: u8) {
+        if key.is_ascii_digit() {
+            let digit = (key - b'0') as usize;
+            self.digits = Some(self.digits.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            return;
+        }
+
+        // digits typed before a key are its count, or the frame to jump to
+        let count = self.digits.take();
+        match key {
+            b' ' | b'p' => self.paused = !self.paused,
+            b'n' | b'.' => {
+                self.paused = true;
+                self.steps += count.unwrap_or(1);
+            }
+            b'+' | b'=' => self.fps = (self.fps * 2).min(MAX_FPS),
+            b'-' | b'_' => self.fps = (self.fps / 2).max(1),
+            b'g' => self.jump = Some(count.unwrap_or(0)),
+            // ctrl-c comes in as a key since signals are off while playing
+            b'q' | 3 => self.quit = true,
+            _ => (),
+        }
+    }
+
+    // steps to run before the next frame is drawn
+    fn take_steps(&mut self) -> usize {
+        if self.paused {
+            std::mem::take(&mut self.steps)
+        } else {
+            (self.fps / DRAW_FPS).max(1) as usize
+        }
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_secs(1) / self.fps.min(DRAW_FPS)
+    }
+}
+
+// a simulation and the steps it ran, started over to go back
+struct Player<F> {
+    start: F,
+    simulation: Box<dyn Simulation>,
+    frame: usize,
+}
+
+impl<F: FnMut() -> Result<Box<dyn Simulation>>> Player<F> {
+    fn new(mut start: F) -> Result<Self> {
+        let simulation = start()?;
+
+        Ok(Player { start, simulation, frame: 0 })
+    }
+
+    fn advance(&mut self, steps: usize) {
+        for _ in 0..steps {
+            if self.simulation.is_done() {
+                break;
+            }
+            self.simulation.step();
+            self.frame += 1;
+        }
+    }
+
+    // stops early when the simulation ends before `frame`
+    fn jump(&mut self, frame: usize) -> Result<()> {
+        if frame < self.frame {
+            self.simulation = (self.start)()?;
+            self.frame = 0;
+        }
+        self.advance(frame - self.frame);
+
+        Ok(())
+    }
+}
+
+// the controlling terminal in raw mode, restored when dropped
+struct Terminal {
+    tty: File,
+    saved: String,
+}
+
+impl Terminal {
+    fn open() -> Option<Self> {
+        let tty = File::open("/dev/tty").ok()?;
+        let saved = stty(&tty, &["-g"]).ok()?;
+        // keys come in one by one without waiting, and are not echoed
+        stty(&tty, &["-icanon", "-echo", "-isig", "min", "0", "time", "0"]).ok()?;
+        print!("\x1b[?25l");
+
+        Some(Terminal { tty, saved })
+    }
+
+    fn keys(&mut self) -> Result<Vec<u8>> {
+        let mut keys = [0; 64];
+        let read = self.tty.read(&mut keys)?;
+
+        Ok(keys[..read].to_vec())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = stty(&self.tty, &[&self.saved]);
+        println!("\x1b[?25h");
+    }
+}
+
+fn stty(tty: &File, args: &[&str]) -> Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::from(tty.try_clone()?)).output()?;
+    if !output.status.success() {
+        bail!("stty {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// redraws in place, clearing what is left of each line and below the frame
+fn draw(frame: &str, status: &str) -> Result<()> {
+    let mut out = String::from("\x1b[H");
+    for line in frame.lines().chain(status.lines()) {
+        out.push_str(line);
+        out.push_str("\x1b[K\n");
+    }
+    out.push_str("\x1b[J");
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Plays the simulations made by `start` in the terminal, taking keys from it when there is one.
+///
+/// Going back to an earlier frame starts a new simulation and runs it up to that frame.
+pub fn play(start: impl FnMut() -> Result<Box<dyn Simulation>>, options: Options) -> Result<()> {
+    let mut terminal = Terminal::open();
+    let mut controls = Controls::new(options);
+    let mut player = Player::new(start)?;
+    let mut drawn = None;
+
+    print!("\x1b[2J");
+    loop {
+        if let Some(terminal) = terminal.as_mut() {
+            for key in terminal.keys()? {
+                controls.press(key);
+            }
+        }
+        if controls.quit {
+            break;
+        }
+
+        match controls.jump.take() {
+            Some(frame) => player.jump(frame)?,
+            None => player.advance(controls.take_steps()),
+        }
+
+        let done = player.simulation.is_done();
+        let state = match (done, controls.paused) {
+            (true, _) => "done",
+            (false, true) => "paused",
+            (false, false) => "playing",
+        };
+        let typed = controls.digits.map(|digits| format!(" | {digits}_")).unwrap_or_default();
+        let mut status = player.simulation.status();
+        if !status.is_empty() {
+            status.push('\n');
+        }
+        status += &format!("frame {} | {} fps | {state}{typed} | {HELP}", player.frame, controls.fps);
+        if drawn.as_ref() != Some(&status) {
+            draw(&player.simulation.render(), &status)?;
+            drawn = Some(status);
+        }
+
+        // without a terminal to take keys from, there is nothing left to do once done
+        if done && terminal.is_none() {
+            break;
+        }
+        thread::sleep(controls.delay());
+    }
+
+    Ok(())
+}
+
+/// Plays `part` of `day` on the input from `source`.
+pub fn run(day: &Day, part: Part, source: &Source, options: Options) -> Result<()> {
+    let input = source.load(day.number)?;
+
+    play(|| day.simulation(&input, part), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let mut controls = Controls::new(Options { frame: 12, ..Options::default() });
+        assert_eq!(Some(12), controls.jump.take());
+
+        for key in b"+++-" {
+            controls.press(*key);
+        }
+        assert_eq!(DEFAULT_FPS * 4, controls.fps);
+        assert_eq!(1, controls.take_steps());
+
+        controls.press(b' ');
+        assert_eq!(0, controls.take_steps());
+        for key in b"n3n" {
+            controls.press(*key);
+        }
+        assert_eq!(4, controls.take_steps());
+        assert_eq!(0, controls.take_steps());
+
+        for key in b"1204g" {
+            controls.press(*key);
+        }
+        assert_eq!(Some(1204), controls.jump.take());
+        controls.press(b'g');
+        assert_eq!(Some(0), controls.jump);
+
+        controls.press(b'1');
+        assert_eq!(Some(1), controls.digits);
+        controls.press(b'x');
+        assert_eq!(None, controls.digits);
+        assert!(!controls.quit);
+        controls.press(b'q');
+        assert!(controls.quit);
+
+        let mut fast = Controls::new(Options { fps: 1000, ..Options::default() });
+        assert_eq!(20, fast.take_steps());
+        assert_eq!(Duration::from_millis(20), fast.delay());
+    }
+
+    struct Counter(usize);
+
+    impl Simulation for Counter {
+        fn step(&mut self) {
+            self.0 = (self.0 + 1).min(10);
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 10
+        }
+
+        fn render(&self) -> String {
+            format!("{}\n", self.0)
+        }
+    }
+
+    #[test]
+    fn jumps_back_by_starting_over() -> Result<()> {
+        let mut starts = 0;
+        let mut player = Player::new(|| {
+            starts += 1;
+            Ok(Box::new(Counter(0)) as Box<dyn Simulation>)
+        })?;
+
+        player.advance(3);
+        player.jump(7)?;
+        assert_eq!((7, "7\n".to_string()), (player.frame, player.simulation.render()));
+
+        player.jump(2)?;
+        assert_eq!((2, "2\n".to_string()), (player.frame, player.simulation.render()));
+
+        player.jump(50)?;
+        assert_eq!(10, player.frame);
+        assert!(player.simulation.is_done());
+
+        drop(player);
+        assert_eq!(2, starts);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{Params, INPUTS_DIR},
+    solution::Part,
+};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Expected {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    part1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    part2: Option<String>,
+    #[serde(default, skip_serializing_if = "Params::is_empty")]
+    params: Params,
+}
+
+impl Expected {
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+// day ("day01") -> input name ("real") -> expected answers
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Answers(BTreeMap<String, BTreeMap<String, Expected>>);
+
+impl Answers {
+    pub fn path() -> PathBuf {
+        PathBuf::from(INPUTS_DIR).join("answers.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::read(&Self::path())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&raw).wrap_err_with(|| format!("parsing {}", path.display()))
+    }
+
+    /// Pretty printed, the way the file is kept.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// An entry without answers for `input` of `day`, unless it has one already.
+    pub fn add(&mut self, day: u8, input: &str) {
+        self.0.entry(format!("day{day:02}")).or_default().entry(input.to_owned()).or_default();
+    }
+
+    pub fn get(&self, day: u8, input: &str, part: Part) -> Option<&str> {
+        self.0.get(&format!("day{day:02}"))?.get(input)?.get(part)
+    }
+
+    /// The parameters `input` of `day` is read with, none if it sets none.
+    pub fn params(&self, day: u8, input: &str) -> Params {
+        self.0
+            .get(&format!("day{day:02}"))
+            .and_then(|inputs| inputs.get(input))
+            .map(|expected| expected.params.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let answers: Answers = serde_json::from_str(
+            r#"{ "day01": { "real": { "part1": "42" }, "example1": { "part2": "7", "params": { "row": 10 } } } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("42"), answers.get(1, "real", Part::One));
+        assert_eq!(None, answers.get(1, "real", Part::Two));
+        assert_eq!(Some("7"), answers.get(1, "example1", Part::Two));
+        assert_eq!(None, answers.get(2, "real", Part::One));
+        assert_eq!(Params::from([("row".to_owned(), 10)]), answers.params(1, "example1"));
+        assert!(answers.params(1, "real").is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    fs,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use eyre::{bail, eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    history::{self, Entry, History},
+    input::{Puzzle, Source},
+    registry::{self, Day},
+    solution::Solution,
+};
+
+pub const DEFAULT_RUNS: usize = 10;
+pub const DEFAULT_REPORT: &str = "target/bench.json";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub runs: usize,
+    pub source: Source,
+    pub output: PathBuf,
+    pub history: PathBuf,
+    /// Flag the phases slower than this many percent since the previous revision.
+    pub compare: Option<f64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            runs: DEFAULT_RUNS,
+            source: Source::Default,
+            output: DEFAULT_REPORT.into(),
+            history: history::DEFAULT_HISTORY.into(),
+            compare: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let nanos = |d: Duration| d.as_nanos() as u64;
+
+        Stats {
+            min_ns: samples.first().copied().map(nanos).unwrap_or_default(),
+            median_ns: samples.get(samples.len() / 2).copied().map(nanos).unwrap_or_default(),
+            max_ns: samples.last().copied().map(nanos).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timings {
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl Timings {
+    pub fn phases(&self) -> [(&'static str, Stats); 3] {
+        [("parse", self.parse), ("part1", self.part1), ("part2", self.part2)]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayReport {
+    pub day: u8,
+    #[serde(flatten)]
+    pub timings: Timings,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub runs: usize,
+    pub days: Vec<DayReport>,
+}
+
+fn time<T>(runs: usize, mut f: impl FnMut() -> T) -> Stats {
+    let samples = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+
+    Stats::new(samples)
+}
+
+pub fn measure<S: Solution>(puzzle: &Puzzle, runs: usize) -> Result<Timings> {
+    let parsed = S::parse_with(&puzzle.text, &puzzle.params)?;
+
+    Ok(Timings {
+        parse: time(runs, || S::parse_with(black_box(&puzzle.text), &puzzle.params)),
+        part1: time(runs, || S::part1(black_box(&parsed))),
+        part2: time(runs, || S::part2(black_box(&parsed))),
+    })
+}
+
+pub fn run(day: Option<u8>, options: &Options) -> Result<()> {
+    let days: Vec<&Day> = match day {
+        Some(number) => vec![registry::find(number).ok_or_else(|| eyre!("unknown day {number}"))?],
+        None => registry::DAYS.iter().collect(),
+    };
+
+    let mut report = Report { runs: options.runs, days: vec![] };
+    for day in days {
+        let input = options.source.load(day.number)?;
+        let timings = day.bench(&input, options.runs).wrap_err_with(|| format!("day {}", day.number))?;
+
+        report.days.push(DayReport { day: day.number, timings });
+    }
+
+    println!("day  phase  {:>12}  {:>12}  {:>12}", "min", "median", "max");
+    for day in &report.days {
+        for (phase, stats) in day.timings.phases() {
+            let show = |ns| format!("{:.2?}", Duration::from_nanos(ns));
+            println!(
+                "{:02}   {phase}  {:>12}  {:>12}  {:>12}",
+                day.day,
+                show(stats.min_ns),
+                show(stats.median_ns),
+                show(stats.max_ns),
+            );
+        }
+    }
+
+    let output = options.output.as_path();
+    if let Some(dir) = output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(&report)?;
+    fs::write(output, json + "\n").wrap_err_with(|| format!("writing {}", output.display()))?;
+    println!("\nreport written to {}", output.display());
+
+    record(report, options)
+}
+
+// keeps the report in the history, then compares it with the previous revision if asked to
+fn record(report: Report, options: &Options) -> Result<()> {
+    let mut history = History::load(&options.history)?;
+    let entry = Entry { revision: history::revision(), input: options.source.id(), runs: report.runs, days: report.days };
+
+    // compare only the days of this run, even once merged with earlier ones at this revision
+    let current = entry.clone();
+    history.record(entry);
+
+    let regressions = match options.compare {
+        Some(threshold) => history::compare(&history, &current, threshold),
+        None => vec![],
+    };
+    history.save(&options.history)?;
+    println!("history written to {}", options.history.display());
+
+    if !regressions.is_empty() {
+        let phases: Vec<String> = regressions
+            .iter()
+            .map(|r| format!("day {:02} {} {:+.1}%", r.day, r.phase, r.percent()))
+            .collect();
+        bail!("{} regression(s): {}", regressions.len(), phases.join(", "));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let samples = [5, 1, 4, 2, 3].map(Duration::from_nanos).to_vec();
+        assert_eq!(Stats { min_ns: 1, median_ns: 3, max_ns: 5 }, Stats::new(samples));
+
+        assert_eq!(Stats { min_ns: 0, median_ns: 0, max_ns: 0 }, Stats::new(vec![]));
+    }
+
+    #[test]
+    fn measures_every_phase() -> Result<()> {
+        let input = Source::Named("example1".into()).load(1)?;
+        let timings = measure::<crate::day01::Day01>(&input, 3)?;
+
+        assert!(timings.parse.min_ns <= timings.parse.median_ns);
+        assert!(timings.part2.median_ns <= timings.part2.max_ns);
+
+        Ok(())
+    }
+}
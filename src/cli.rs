@@ -0,0 +1,474 @@
+use eyre::{bail, eyre, Result, WrapErr};
+
+use adventofcode2022::{animate, bench, crosscheck, generate, history, image, input::Source, log::Level, runner::Format, solution::Part};
+
+pub const USAGE: &str = "\
+usage: adventofcode2022 [run] <day> <part> [--input NAME|PATH|-] [--format text|json]
+       adventofcode2022 run --all [--input NAME] [--format text|json]
+       adventofcode2022 verify
+       adventofcode2022 scaffold <day>
+       adventofcode2022 bench [day] [--runs N] [--input NAME] [--output PATH] [--history PATH]
+                              [--compare [--threshold PERCENT]]
+       adventofcode2022 animate <day> [part] [--input NAME|PATH|-] [--fps N] [--frame N] [--paused]
+       adventofcode2022 export <day> [part] [--input NAME|PATH|-] [--image ppm|svg] [--scale N]
+                               [--frame N | --every N --max-frames N] [--output PATH]
+       adventofcode2022 generate <day> [--seed N] [--size N]
+       adventofcode2022 crosscheck [day] [--runs N]
+
+every command takes -v, -vv or -vvv (or --verbose) to log info, debug or trace to stderr";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run { day: u8, part: Part, source: Source, format: Format },
+    RunAll { source: Source, format: Format },
+    Verify,
+    Scaffold { day: u8 },
+    Bench { day: Option<u8>, options: bench::Options },
+    Animate { day: u8, part: Part, source: Source, options: animate::Options },
+    Export { day: u8, part: Part, source: Source, export: image::Export },
+    Generate { day: u8, seed: u64, size: usize },
+    Crosscheck { day: Option<u8>, runs: u64 },
+}
+
+impl Command {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+
+        match args.peek().map(String::as_str) {
+            Some("verify") => {
+                args.next();
+                if let Some(arg) = args.next() {
+                    bail!("unexpected argument {arg:?}\n{USAGE}");
+                }
+                Ok(Command::Verify)
+            }
+            Some("scaffold") => {
+                args.next();
+                let (Some(day), None) = (args.next(), args.next()) else {
+                    bail!(USAGE);
+                };
+                let day = day.parse().wrap_err_with(|| format!("invalid day {day:?}\n{USAGE}"))?;
+                Ok(Command::Scaffold { day })
+            }
+            Some("bench") => {
+                args.next();
+                bench_args(args)
+            }
+            Some("animate") => {
+                args.next();
+                animate_args(args)
+            }
+            Some("export") => {
+                args.next();
+                export_args(args)
+            }
+            Some("generate") => {
+                args.next();
+                generate_args(args)
+            }
+            Some("crosscheck") => {
+                args.next();
+                crosscheck_args(args)
+            }
+            Some("run") => {
+                args.next();
+                run(args)
+            }
+            _ => run(args),
+        }
+    }
+}
+
+// `-v` flags can go anywhere, each one raises the level by one
+pub fn verbosity(args: impl IntoIterator<Item = String>) -> (Level, Vec<String>) {
+    let mut count = 0;
+    let rest = args
+        .into_iter()
+        .filter(|arg| match arg.strip_prefix('-') {
+            Some(flags) if !flags.is_empty() && flags.chars().all(|c| c == 'v') => {
+                count += flags.len();
+                false
+            }
+            _ if arg.as_str() == "--verbose" => {
+                count += 1;
+                false
+            }
+            _ => true,
+        })
+        .collect();
+
+    (Level::from_verbosity(count), rest)
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut positional = vec![];
+    let mut source = Source::Default;
+    let mut all = false;
+    let mut format = Format::Text;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--input" => {
+                let path = args.next().ok_or_else(|| eyre!("--input needs a path\n{USAGE}"))?;
+                source = Source::from(path.as_str());
+            }
+            "--format" => {
+                format = args.next().ok_or_else(|| eyre!("--format needs `text` or `json`\n{USAGE}"))?.parse()?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    if all {
+        if let Some(arg) = positional.first() {
+            bail!("unexpected argument {arg:?} with --all\n{USAGE}");
+        }
+        // every day reads its own input, so only the default or a fixture name make sense
+        if !matches!(source, Source::Default | Source::Named(_)) {
+            bail!("--all only takes a fixture name as --input\n{USAGE}");
+        }
+        return Ok(Command::RunAll { source, format });
+    }
+
+    let [day, part] = positional.as_slice() else {
+        bail!(USAGE);
+    };
+    let day: u8 = day.parse().wrap_err_with(|| format!("invalid day {day:?}\n{USAGE}"))?;
+    let part: u8 = part.parse().wrap_err_with(|| format!("invalid part {part:?}\n{USAGE}"))?;
+    let part = Part::try_from(part).map_err(|e| eyre!("{e} for day {day}"))?;
+
+    Ok(Command::Run { day, part, source, format })
+}
+
+fn bench_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut day = None;
+    let mut options = bench::Options::default();
+    let mut compare = false;
+    let mut threshold = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => {
+                let value = args.next().ok_or_else(|| eyre!("--runs needs a count\n{USAGE}"))?;
+                options.runs = value.parse().wrap_err_with(|| format!("invalid run count {value:?}\n{USAGE}"))?;
+                if options.runs == 0 {
+                    bail!("--runs must be at least 1");
+                }
+            }
+            "--input" => {
+                let path = args.next().ok_or_else(|| eyre!("--input needs a fixture name\n{USAGE}"))?;
+                options.source = Source::from(path.as_str());
+            }
+            "--output" => {
+                options.output = args.next().ok_or_else(|| eyre!("--output needs a path\n{USAGE}"))?.into();
+            }
+            "--history" => {
+                options.history = args.next().ok_or_else(|| eyre!("--history needs a path\n{USAGE}"))?.into();
+            }
+            "--compare" => compare = true,
+            "--threshold" => {
+                let value = args.next().ok_or_else(|| eyre!("--threshold needs a percentage\n{USAGE}"))?;
+                let percent: f64 = value.parse().wrap_err_with(|| format!("invalid threshold {value:?}\n{USAGE}"))?;
+                if percent.is_nan() || percent < 0.0 {
+                    bail!("--threshold must be a percentage of at least 0");
+                }
+                threshold = Some(percent);
+            }
+            _ if day.is_none() => {
+                day = Some(arg.parse().wrap_err_with(|| format!("invalid day {arg:?}\n{USAGE}"))?);
+            }
+            _ => bail!("unexpected argument {arg:?}\n{USAGE}"),
+        }
+    }
+    // every day reads its own input, as with `run --all`
+    if !matches!(options.source, Source::Default | Source::Named(_)) {
+        bail!("bench only takes a fixture name as --input\n{USAGE}");
+    }
+    options.compare = match (compare, threshold) {
+        (true, threshold) => Some(threshold.unwrap_or(history::DEFAULT_THRESHOLD)),
+        (false, None) => None,
+        (false, Some(_)) => bail!("--threshold only goes with --compare\n{USAGE}"),
+    };
+
+    Ok(Command::Bench { day, options })
+}
+
+fn animate_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut positional = vec![];
+    let mut source = Source::Default;
+    let mut options = animate::Options::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or_else(|| eyre!("--input needs a path\n{USAGE}"))?;
+                source = Source::from(path.as_str());
+            }
+            "--fps" => {
+                let value = args.next().ok_or_else(|| eyre!("--fps needs a rate\n{USAGE}"))?;
+                options.fps = value.parse().wrap_err_with(|| format!("invalid frame rate {value:?}\n{USAGE}"))?;
+                if options.fps == 0 {
+                    bail!("--fps must be at least 1");
+                }
+            }
+            "--frame" => {
+                let value = args.next().ok_or_else(|| eyre!("--frame needs a frame number\n{USAGE}"))?;
+                options.frame = value.parse().wrap_err_with(|| format!("invalid frame {value:?}\n{USAGE}"))?;
+            }
+            "--paused" => options.paused = true,
+            _ => positional.push(arg),
+        }
+    }
+
+    let (day, part) = day_and_part(&positional)?;
+
+    Ok(Command::Animate { day, part, source, options })
+}
+
+fn export_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut positional = vec![];
+    let mut source = Source::Default;
+    let mut export = image::Export::default();
+
+    while let Some(arg) = args.next() {
+        let mut count = |flag: &str, what: &str| -> Result<usize> {
+            let value = args.next().ok_or_else(|| eyre!("{flag} needs {what}\n{USAGE}"))?;
+            value.parse().wrap_err_with(|| format!("invalid {flag} {value:?}\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or_else(|| eyre!("--input needs a path\n{USAGE}"))?;
+                source = Source::from(path.as_str());
+            }
+            "--image" => {
+                export.format = args.next().ok_or_else(|| eyre!("--image needs `ppm` or `svg`\n{USAGE}"))?.parse()?;
+            }
+            "--scale" => export.scale = count("--scale", "a number of pixels per cell")?,
+            "--frame" => export.frame = Some(count("--frame", "a frame number")?),
+            "--every" => export.every = Some(count("--every", "a number of frames")?),
+            "--max-frames" => export.max_frames = Some(count("--max-frames", "a frame number")?),
+            "--output" => {
+                export.output = Some(args.next().ok_or_else(|| eyre!("--output needs a path\n{USAGE}"))?.into());
+            }
+            _ => positional.push(arg),
+        }
+    }
+    if export.scale == 0 || export.every == Some(0) {
+        bail!("--scale and --every must be at least 1");
+    }
+    if export.frame.is_some() && (export.every.is_some() || export.max_frames.is_some()) {
+        bail!("--frame picks a single image, it does not go with --every or --max-frames\n{USAGE}");
+    }
+    if export.every.is_some() && export.max_frames.is_none() {
+        bail!("--every needs --max-frames, the frame to stop at\n{USAGE}");
+    }
+    let (day, part) = day_and_part(&positional)?;
+
+    Ok(Command::Export { day, part, source, export })
+}
+
+fn generate_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut day = None;
+    let mut seed = 0;
+    let mut size = generate::DEFAULT_SIZE;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or_else(|| eyre!("--seed needs a number\n{USAGE}"))?;
+                seed = value.parse().wrap_err_with(|| format!("invalid seed {value:?}\n{USAGE}"))?;
+            }
+            "--size" => {
+                let value = args.next().ok_or_else(|| eyre!("--size needs a number\n{USAGE}"))?;
+                size = value.parse().wrap_err_with(|| format!("invalid size {value:?}\n{USAGE}"))?;
+                if size == 0 {
+                    bail!("--size must be at least 1");
+                }
+            }
+            _ if day.is_none() => {
+                day = Some(arg.parse().wrap_err_with(|| format!("invalid day {arg:?}\n{USAGE}"))?);
+            }
+            _ => bail!("unexpected argument {arg:?}\n{USAGE}"),
+        }
+    }
+    let day = day.ok_or_else(|| eyre!(USAGE))?;
+
+    Ok(Command::Generate { day, seed, size })
+}
+
+fn crosscheck_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut day = None;
+    let mut runs = crosscheck::DEFAULT_RUNS;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => {
+                let value = args.next().ok_or_else(|| eyre!("--runs needs a count\n{USAGE}"))?;
+                runs = value.parse().wrap_err_with(|| format!("invalid run count {value:?}\n{USAGE}"))?;
+            }
+            _ if day.is_none() => {
+                day = Some(arg.parse().wrap_err_with(|| format!("invalid day {arg:?}\n{USAGE}"))?);
+            }
+            _ => bail!("unexpected argument {arg:?}\n{USAGE}"),
+        }
+    }
+
+    Ok(Command::Crosscheck { day, runs })
+}
+
+// `<day> [part]`, part 1 when left out
+fn day_and_part(positional: &[String]) -> Result<(u8, Part)> {
+    let (day, part) = match positional {
+        [day] => (day, None),
+        [day, part] => (day, Some(part)),
+        _ => bail!(USAGE),
+    };
+    let day: u8 = day.parse().wrap_err_with(|| format!("invalid day {day:?}\n{USAGE}"))?;
+    let part = match part {
+        Some(part) => {
+            let part: u8 = part.parse().wrap_err_with(|| format!("invalid part {part:?}\n{USAGE}"))?;
+            Part::try_from(part).map_err(|e| eyre!("{e} for day {day}"))?
+        }
+        None => Part::One,
+    };
+
+    Ok((day, part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command> {
+        Command::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn commands() -> Result<()> {
+        let run = Command::Run { day: 4, part: Part::Two, source: Source::Default, format: Format::Text };
+        assert_eq!(run, parse(&["4", "2"])?);
+        assert_eq!(run, parse(&["run", "4", "2"])?);
+
+        assert_eq!(
+            Command::Run { day: 4, part: Part::One, source: Source::Stdin, format: Format::Json },
+            parse(&["4", "--input", "-", "1", "--format", "json"])?
+        );
+        assert_eq!(Command::RunAll { source: Source::Default, format: Format::Text }, parse(&["run", "--all"])?);
+        assert_eq!(
+            Command::RunAll { source: Source::Named("example1".into()), format: Format::Text },
+            parse(&["--all", "--input", "example1"])?
+        );
+        assert!(parse(&["run", "--all", "--input", "-"]).is_err());
+        assert!(parse(&["run", "--all", "4"]).is_err());
+        assert!(parse(&["run", "--all", "--format", "xml"]).is_err());
+
+        assert_eq!(Command::Verify, parse(&["verify"])?);
+        assert_eq!(Command::Scaffold { day: 20 }, parse(&["scaffold", "20"])?);
+
+        assert!(parse(&["4"]).is_err());
+        assert!(parse(&["4", "3"]).is_err());
+        assert!(parse(&["verify", "4"]).is_err());
+        assert!(parse(&["scaffold"]).is_err());
+
+        assert_eq!(Command::Bench { day: None, options: bench::Options::default() }, parse(&["bench"])?);
+        assert_eq!(
+            Command::Bench {
+                day: Some(16),
+                options: bench::Options { runs: 3, output: "out.json".into(), ..bench::Options::default() },
+            },
+            parse(&["bench", "--runs", "3", "16", "--output", "out.json"])?
+        );
+        assert_eq!(
+            Command::Bench {
+                day: None,
+                options: bench::Options {
+                    source: Source::Named("example1".into()),
+                    history: "history.json".into(),
+                    compare: Some(history::DEFAULT_THRESHOLD),
+                    ..bench::Options::default()
+                },
+            },
+            parse(&["bench", "--compare", "--input", "example1", "--history", "history.json"])?
+        );
+        assert_eq!(
+            Command::Bench { day: Some(17), options: bench::Options { compare: Some(2.5), ..bench::Options::default() } },
+            parse(&["bench", "17", "--threshold", "2.5", "--compare"])?
+        );
+        assert!(parse(&["bench", "--runs", "0"]).is_err());
+        assert!(parse(&["bench", "1", "2"]).is_err());
+        assert!(parse(&["bench", "--threshold", "5"]).is_err());
+        assert!(parse(&["bench", "--compare", "--threshold", "-5"]).is_err());
+        assert!(parse(&["bench", "--input", "-"]).is_err());
+
+        assert_eq!(
+            Command::Animate { day: 14, part: Part::One, source: Source::Default, options: animate::Options::default() },
+            parse(&["animate", "14"])?
+        );
+        assert_eq!(
+            Command::Animate {
+                day: 17,
+                part: Part::Two,
+                source: Source::Named("example1".into()),
+                options: animate::Options { fps: 200, frame: 40, paused: true },
+            },
+            parse(&["animate", "17", "2", "--fps", "200", "--input", "example1", "--frame", "40", "--paused"])?
+        );
+        assert!(parse(&["animate"]).is_err());
+        assert!(parse(&["animate", "9", "3"]).is_err());
+        assert!(parse(&["animate", "9", "--fps", "0"]).is_err());
+
+        assert_eq!(
+            Command::Export { day: 12, part: Part::One, source: Source::Default, export: image::Export::default() },
+            parse(&["export", "12"])?
+        );
+        assert_eq!(
+            Command::Export {
+                day: 14,
+                part: Part::Two,
+                source: Source::Default,
+                export: image::Export {
+                    format: image::ImageFormat::Svg,
+                    scale: 2,
+                    frame: None,
+                    every: Some(500),
+                    max_frames: Some(2000),
+                    output: Some("sand".into()),
+                },
+            },
+            parse(&["export", "14", "2", "--image", "svg", "--every", "500", "--max-frames", "2000", "--scale", "2", "--output", "sand"])?
+        );
+        assert!(parse(&["export", "14", "--image", "png"]).is_err());
+        assert!(parse(&["export", "14", "--frame", "3", "--every", "2"]).is_err());
+        assert!(parse(&["export", "14", "--frame", "3", "--max-frames", "2"]).is_err());
+        assert!(parse(&["export", "14", "--scale", "0"]).is_err());
+        assert!(parse(&["export", "14", "--every", "500"]).is_err());
+
+        assert_eq!(Command::Generate { day: 5, seed: 0, size: generate::DEFAULT_SIZE }, parse(&["generate", "5"])?);
+        assert_eq!(
+            Command::Generate { day: 11, seed: 42, size: 3 },
+            parse(&["generate", "--size", "3", "11", "--seed", "42"])?
+        );
+        assert!(parse(&["generate"]).is_err());
+        assert!(parse(&["generate", "5", "--size", "0"]).is_err());
+        assert!(parse(&["generate", "5", "--seed", "-1"]).is_err());
+
+        assert_eq!(Command::Crosscheck { day: None, runs: crosscheck::DEFAULT_RUNS }, parse(&["crosscheck"])?);
+        // no generated inputs, only the ones in `inputs/`
+        assert_eq!(Command::Crosscheck { day: Some(15), runs: 0 }, parse(&["crosscheck", "--runs", "0", "15"])?);
+        assert!(parse(&["crosscheck", "1", "18"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verbosity_flags() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert_eq!((Level::Quiet, args(&["4", "2"])), verbosity(args(&["4", "2"])));
+        assert_eq!((Level::Info, args(&["4", "2"])), verbosity(args(&["-v", "4", "2"])));
+        assert_eq!((Level::Debug, args(&["run", "--all"])), verbosity(args(&["run", "-v", "--all", "--verbose"])));
+        assert_eq!((Level::Trace, args(&["verify"])), verbosity(args(&["verify", "-vvvv"])));
+        assert_eq!((Level::Quiet, args(&["4", "-", "-x"])), verbosity(args(&["4", "-", "-x"])));
+    }
+}
@@ -0,0 +1,165 @@
+use eyre::{bail, eyre, Result};
+use itertools::Itertools;
+
+use crate::{
+    generate,
+    input::{self, Puzzle, Source},
+    registry::{self, Day},
+    runner,
+    solution::{Answer, Part, Solution},
+    verify::short,
+};
+
+pub const DEFAULT_RUNS: u64 = 20;
+
+/// A way to solve a part, by name.
+pub type Implementation<I, A> = (&'static str, fn(&I) -> A);
+
+/// A way to solve a part from the input text, reading it on its own rather than through `parse`.
+pub type FromText<A> = (&'static str, fn(&str) -> A);
+
+/// Days that solve a part in more than one way, such as `naive`, `fast` or `parallel`, so each
+/// way can be checked against the others.
+pub trait Variants: Solution<Input: 'static, Answer1: 'static, Answer2: 'static> {
+    /// Every implementation of part 1, `part1` first.
+    const PART1: &'static [Implementation<Self::Input, Self::Answer1>];
+    /// Every implementation of part 2, `part2` first.
+    const PART2: &'static [Implementation<Self::Input, Self::Answer2>];
+    /// The implementations of part 1 that read the text themselves, run after those of `PART1`.
+    const TEXT_PART1: &'static [FromText<Self::Answer1>] = &[];
+    /// The implementations of part 2 that read the text themselves, run after those of `PART2`.
+    const TEXT_PART2: &'static [FromText<Self::Answer2>] = &[];
+}
+
+/// The answer of every implementation of `part` by name, a panic being an error.
+pub type Answers = Vec<(&'static str, Result<String>)>;
+
+pub fn answers<S: Variants>(puzzle: &Puzzle, part: Part) -> Result<Answers> {
+    let input = S::parse_with(&puzzle.text, &puzzle.params)?;
+    let text = puzzle.text.as_str();
+
+    Ok(match part {
+        Part::One => S::PART1
+            .iter()
+            .map(|&(name, f)| (name, runner::catch(|| f(&input).answer())))
+            .chain(S::TEXT_PART1.iter().map(|&(name, f)| (name, runner::catch(|| f(text).answer()))))
+            .collect(),
+        Part::Two => S::PART2
+            .iter()
+            .map(|&(name, f)| (name, runner::catch(|| f(&input).answer())))
+            .chain(S::TEXT_PART2.iter().map(|&(name, f)| (name, runner::catch(|| f(text).answer()))))
+            .collect(),
+    })
+}
+
+pub struct Check {
+    pub day: u8,
+    pub input: String,
+    pub part: Part,
+    pub answers: Answers,
+}
+
+impl Check {
+    pub fn agrees(&self) -> bool {
+        self.answers.iter().all(|(_, answer)| answer.is_ok()) && self.answers.iter().map(|(_, answer)| answer.as_ref().ok()).all_equal()
+    }
+}
+
+/// The inputs of `day` from `inputs/`, then the generated ones of seeds `0..runs`, by name.
+pub fn inputs(day: &Day, runs: u64) -> Result<Vec<(String, Puzzle)>> {
+    let mut inputs = vec![];
+    for (name, _) in input::named_inputs(day.number)? {
+        let puzzle = Source::Named(name.clone()).load(day.number)?;
+        inputs.push((name, puzzle));
+    }
+    for seed in 0..runs {
+        let size = 1 + (seed as usize) % generate::DEFAULT_SIZE;
+        inputs.push((format!("seed {seed} size {size}"), day.generate(seed, size)));
+    }
+
+    Ok(inputs)
+}
+
+/// Runs every implementation of both parts of `day` on each of `inputs`.
+pub fn check_day(day: &Day, inputs: Vec<(String, Puzzle)>) -> Result<Vec<Check>> {
+    let mut checks = vec![];
+    for (name, input) in inputs {
+        for part in Part::ALL {
+            let answers = day.variants(&input, part).map_err(|e| eyre!("day {} on {name}: {e:#}", day.number))?;
+
+            checks.push(Check { day: day.number, input: name.clone(), part, answers });
+        }
+    }
+
+    Ok(checks)
+}
+
+pub fn run(day: Option<u8>, runs: u64) -> Result<()> {
+    let days: Vec<&Day> = match day {
+        Some(number) => vec![registry::find_with_variants(number)?],
+        None => registry::DAYS.iter().filter(|day| day.has_variants()).collect(),
+    };
+
+    let mut checks = vec![];
+    for day in days {
+        checks.extend(check_day(day, inputs(day, runs)?)?);
+    }
+
+    let disagreements: Vec<&Check> = checks.iter().filter(|check| !check.agrees()).collect();
+    if !disagreements.is_empty() {
+        println!("day  part  input  answers");
+    }
+    for check in &disagreements {
+        let answers = check
+            .answers
+            .iter()
+            .map(|(name, answer)| match answer {
+                Ok(answer) => format!("{name}={}", short(answer)),
+                Err(e) => format!("{name} failed: {e:#}"),
+            })
+            .join(", ");
+
+        println!("{:02}   {}     {}  {answers}", check.day, check.part, check.input);
+    }
+
+    let implementations: usize = checks.iter().map(|check| check.answers.len()).sum();
+    println!(
+        "{} of {} checks agree, {implementations} answers compared",
+        checks.len() - disagreements.len(),
+        checks.len(),
+    );
+
+    if !disagreements.is_empty() {
+        bail!("{} check(s) disagree", disagreements.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_implementation_agrees() -> Result<()> {
+        for day in registry::DAYS.iter().filter(|day| day.has_variants()) {
+            // the naive variants take minutes on the real inputs, `crosscheck` still runs those
+            let inputs = inputs(day, 10)?.into_iter().filter(|(name, _)| name != "real").collect();
+            for check in check_day(day, inputs)? {
+                let answers: Vec<String> = check.answers.iter().map(|(name, answer)| format!("{name}: {answer:?}")).collect();
+                assert!(check.agrees(), "day {} part {} on {}: {answers:?}", check.day, check.part, check.input);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn disagreements() {
+        let check = |answers: Answers| Check { day: 1, input: "example1".into(), part: Part::One, answers }.agrees();
+
+        assert!(check(vec![("fast", Ok("42".into())), ("naive", Ok("42".into()))]));
+        assert!(!check(vec![("fast", Ok("42".into())), ("naive", Ok("41".into()))]));
+        assert!(!check(vec![("fast", Ok("42".into())), ("naive", Err(eyre!("panicked: overflow")))]));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+/// Where a sequence of states starts repeating, counted in steps from the first state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The step up to the end of the first pass through the cycle in the same state as `step`,
+    /// with the number of whole cycles between them.
+    pub fn fold(&self, step: u128) -> (usize, u128) {
+        let start = self.start as u128;
+        if step < start {
+            return (step as usize, 0);
+        }
+        let offset = step - start;
+        let length = self.length as u128;
+
+        (self.start + (offset % length) as usize, offset / length)
+    }
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle of {} steps from step {}", self.length, self.start)
+    }
+}
+
+/// Values that change by the same amount on every pass through a cycle.
+pub trait Metric: Clone {
+    /// `self` moved `cycles` times by the change from `from` to `to`.
+    fn project(&self, from: &Self, to: &Self, cycles: u128) -> Self;
+}
+
+macro_rules! metric {
+    ($($t:ty),+) => {
+        $(
+            impl Metric for $t {
+                fn project(&self, from: &Self, to: &Self, cycles: u128) -> Self {
+                    self + (to - from) * cycles as $t
+                }
+            }
+        )+
+    };
+}
+
+metric!(u32, u64, u128, usize, i32, i64, i128, isize);
+
+impl<T: Metric> Metric for Vec<T> {
+    fn project(&self, from: &Self, to: &Self, cycles: u128) -> Self {
+        self.iter().zip(from).zip(to).map(|((value, from), to)| value.project(from, to, cycles)).collect()
+    }
+}
+
+/// The metric of every step run by [`detect`], and the cycle it found if any.
+#[derive(Debug, Clone)]
+pub struct History<M> {
+    metrics: Vec<M>,
+    cycle: Option<Cycle>,
+}
+
+impl<M: Metric> History<M> {
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// Metric of the initial state followed by one per step run.
+    pub fn metrics(&self) -> &[M] {
+        &self.metrics
+    }
+
+    /// Metric after `steps` steps, extrapolated through the cycle when it was not reached.
+    pub fn extrapolate(&self, steps: u128) -> Option<M> {
+        if let Some(metric) = usize::try_from(steps).ok().and_then(|steps| self.metrics.get(steps)) {
+            return Some(metric.clone());
+        }
+
+        let cycle = self.cycle?;
+        let (step, cycles) = cycle.fold(steps);
+        let (from, to) = (&self.metrics[cycle.start], &self.metrics[cycle.start + cycle.length]);
+
+        Some(self.metrics[step].project(from, to, cycles))
+    }
+}
+
+/// Runs `step` on `state` until two states share a `key` or `limit` steps are done, recording
+/// `metric` before the first step and after each one.
+pub fn detect<S, K, M>(
+    mut state: S,
+    limit: usize,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut metric: impl FnMut(&S) -> M,
+) -> History<M>
+where
+    K: Eq + Hash,
+    M: Metric,
+{
+    let mut seen = HashMap::new();
+    let mut metrics = vec![];
+    loop {
+        let steps = metrics.len();
+        metrics.push(metric(&state));
+        if let Some(start) = seen.insert(key(&state), steps) {
+            return History { metrics, cycle: Some(Cycle { start, length: steps - start }) };
+        }
+        if steps == limit {
+            return History { metrics, cycle: None };
+        }
+
+        step(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolates_like_running_every_step() {
+        // x -> x² + 1 mod 1000, summing every value seen so far
+        let history = detect(
+            (7u64, 0u64),
+            usize::MAX,
+            |(x, sum)| {
+                *x = (*x * *x + 1) % 1000;
+                *sum += *x;
+            },
+            |&(x, _)| x,
+            |&(_, sum)| sum,
+        );
+        let cycle = history.cycle().unwrap();
+        assert!(cycle.length > 1 && cycle.start > 0, "{cycle}");
+
+        let (mut x, mut sum) = (7u64, 0u64);
+        for steps in 0..5000u128 {
+            assert_eq!(Some(sum), history.extrapolate(steps), "after {steps} steps");
+            x = (x * x + 1) % 1000;
+            sum += x;
+        }
+    }
+
+    #[test]
+    fn cycles_and_limits() {
+        // 0, 1, 2, 3, 1, 2, 3, ... counting how often each value was left
+        let history = detect(
+            (0usize, vec![0u64; 4]),
+            100,
+            |(x, counts)| {
+                counts[*x] += 1;
+                *x = if *x == 3 { 1 } else { *x + 1 };
+            },
+            |&(x, _)| x,
+            |(_, counts)| counts.clone(),
+        );
+
+        let cycle = history.cycle().unwrap();
+        assert_eq!(Cycle { start: 1, length: 3 }, cycle);
+        assert_eq!("cycle of 3 steps from step 1", cycle.to_string());
+        assert_eq!((2, 0), cycle.fold(2));
+        assert_eq!((3, 3), cycle.fold(12));
+        assert_eq!(Some(vec![1, 333_333_333_333, 333_333_333_333, 333_333_333_333]), history.extrapolate(1_000_000_000_000));
+
+        let history = detect(0u64, 10, |x| *x += 1, |&x| x, |&x| x);
+        assert_eq!(None, history.cycle());
+        assert_eq!(11, history.metrics().len());
+        assert_eq!(Some(10), history.extrapolate(10));
+        assert_eq!(None, history.extrapolate(11));
+    }
+}
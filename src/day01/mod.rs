@@ -1,114 +1,334 @@
-#[cfg(test)]
-mod tests {
-    use std::{io::{BufRead, BufReader}, fs::File, cmp::Reverse};
-    use itertools::Itertools;
+//! Day 1: Calorie Counting.
 
-    #[derive(Eq, Debug, PartialEq)]
-    struct Elf {
-      food: Vec<i32>
-    }
+use std::{
+  cmp::Reverse,
+  collections::BinaryHeap,
+  fmt::{self, Display},
+  io::BufRead,
+};
 
-    impl PartialOrd for Elf {
-      fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.total().partial_cmp(&other.total())
-      }
-    }
+use eyre::{Result, WrapErr};
+use itertools::Itertools;
 
-    impl Ord for Elf {
-      fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.total().cmp(&other.total())
-      }
-    }
+use crate::{
+  crosscheck::{FromText, Implementation, Variants},
+  generate::{Generate, Rng},
+  log::debug,
+  parse,
+  solution::Solution,
+};
+
+/// The calories each elf carries, read from groups of lines separated by blank lines.
+///
+/// Only the sum of each group is kept, not the items in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inventory {
+  totals: Vec<i32>,
+}
+
+impl Inventory {
+  /// Reads every group, the last one ending with the input as well as with a blank line.
+  pub fn read(reader: impl BufRead) -> Result<Self> {
+    let mut totals = vec![];
+    each_total(reader, |total| totals.push(total))?;
+
+    Ok(Inventory { totals })
+  }
+
+  /// The calories carried by each elf, in the order of the input.
+  pub fn totals(&self) -> &[i32] {
+    &self.totals
+  }
 
-    impl Elf {
-      pub fn new() -> Self {
-        Elf {
-          food: vec![]
-        }
-      }
+  pub fn len(&self) -> usize {
+    self.totals.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.totals.is_empty()
+  }
+
+  /// The calories carried by all the elves together.
+  pub fn total(&self) -> i32 {
+    self.totals.iter().sum()
+  }
+
+  /// The `k` largest totals, largest first.
+  pub fn top_k(&self, k: usize) -> Vec<i32> {
+    let mut largest = Largest::new(k);
+    self.totals.iter().for_each(|&total| largest.push(total));
+
+    largest.into_vec()
+  }
+
+  /// The total that `percent` percent of the elves carry at most, by nearest rank.
+  pub fn percentile(&self, percent: f64) -> Option<i32> {
+    let mut sorted = self.totals.clone();
+    sorted.sort_unstable();
+
+    percentile(&sorted, percent)
+  }
 
-      pub fn total(&self) -> i32 {
-        self.food.iter().sum()
-      }
+  pub fn stats(&self) -> Option<Stats> {
+    let mut sorted = self.totals.clone();
+    sorted.sort_unstable();
+    let (&min, &max) = (sorted.first()?, sorted.last()?);
 
-      pub fn push(&mut self, calories: i32) {
-        self.food.push(calories);
-      }
+    let middle = sorted.len() / 2;
+    let median = match sorted.len() % 2 {
+      0 => (sorted[middle - 1] + sorted[middle]) as f64 / 2.0,
+      _ => sorted[middle] as f64,
+    };
+
+    Some(Stats {
+      elves: sorted.len(),
+      total: self.total(),
+      min,
+      max,
+      mean: self.total() as f64 / sorted.len() as f64,
+      median,
+      p90: percentile(&sorted, 90.0)?,
+    })
+  }
+}
+
+/// A summary of the calories carried by the elves of an [`Inventory`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+  pub elves: usize,
+  pub total: i32,
+  pub min: i32,
+  pub max: i32,
+  pub mean: f64,
+  pub median: f64,
+  pub p90: i32,
+}
+
+impl Display for Stats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} elves carrying {} calories, {} to {}, mean {:.1}, median {:.1}, 90th percentile {}",
+      self.elves, self.total, self.min, self.max, self.mean, self.median, self.p90
+    )
+  }
+}
+
+// nearest rank: the smallest total at least `percent` percent of the elves don't exceed
+fn percentile(sorted: &[i32], percent: f64) -> Option<i32> {
+  let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+
+  sorted.get(rank.max(1) - 1).copied()
+}
+
+/// The `k` largest totals of the input, largest first, read in a single pass that keeps no more
+/// than `k` of them.
+pub fn top_k(reader: impl BufRead, k: usize) -> Result<Vec<i32>> {
+  let mut largest = Largest::new(k);
+  each_total(reader, |total| largest.push(total))?;
+
+  Ok(largest.into_vec())
+}
+
+// calls `f` with each elf's total as soon as its group ends, skipping repeated blank lines
+fn each_total(reader: impl BufRead, mut f: impl FnMut(i32)) -> Result<()> {
+  let mut current: Option<i32> = None;
+
+  for (index, line) in reader.lines().enumerate() {
+    let number = index + 1;
+    let line = line.wrap_err_with(|| format!("reading line {number}"))?;
+    if line.is_empty() {
+      current.take().into_iter().for_each(&mut f);
+      continue;
     }
 
-    #[test]
-    fn adventofcode1() {
-        let file = File::open("./src/day01/input.txt");
-        let file = file.unwrap();
-        let lines = BufReader::new(file).lines();
-
-        let mut elves: Vec<Elf> = vec![];
-        let mut current_elf = Elf::new();
-
-        for line in lines  {
-          if let Ok(line) = line {
-            let calories = line.parse::<i32>();
-
-            match calories {
-                Ok(cal) => current_elf.push(cal),
-                Err(_) => {
-                  elves.push(current_elf);
-                  current_elf = Elf::new();
-                },
-            }
-          }            
-        }
-
-        let max = elves.iter().max();
-        println!("{:?} = {}", max.unwrap(), max.unwrap().total());
-
-        assert_eq!(70116, max.unwrap().total());
-      
+    let calories: i32 = parse::number(&line, "calories or a blank line").wrap_err_with(|| parse::at_line(number, &line))?;
+    *current.get_or_insert(0) += calories;
+  }
+
+  // the input doesn't have to end with a blank line, keep the last elf
+  current.into_iter().for_each(f);
+
+  Ok(())
+}
+
+// the `k` largest totals pushed so far, the smallest of them on top to be replaced first
+struct Largest {
+  k: usize,
+  heap: BinaryHeap<Reverse<i32>>,
+}
+
+impl Largest {
+  fn new(k: usize) -> Self {
+    Largest { k, heap: BinaryHeap::with_capacity(k + 1) }
+  }
+
+  fn push(&mut self, total: i32) {
+    self.heap.push(Reverse(total));
+    if self.heap.len() > self.k {
+      self.heap.pop();
     }
+  }
+
+  fn into_vec(self) -> Vec<i32> {
+    self.heap.into_sorted_vec().into_iter().map(|total| total.0).collect()
+  }
+}
 
-    #[test]
-    fn adventofcode1bis(){
-      let max_of_3 = include_str!("input.txt")
-        .lines()
-        .map(|s| s.parse::<u64>().ok())
-        .batching(|it| it.map_while(|e| e).sum1::<u64>())
-        .map(Reverse)
-        .k_smallest(3)
-        .map(|x| x.0)
-        .sum::<u64>();
-
-      assert_eq!(206582, max_of_3);
+pub struct Day01;
+
+impl Solution for Day01 {
+  type Input = Inventory;
+  type Answer1 = i32;
+  type Answer2 = i32;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    let inventory = Inventory::read(input.as_bytes())?;
+    if let Some(stats) = inventory.stats() {
+      debug!("{stats}");
     }
 
-    #[test]
-    fn adventofcode2() {
-      let file = File::open("./src/day01/input.txt");
-      let file = file.unwrap();
-      let lines = BufReader::new(file).lines();
-
-      let mut elves: Vec<Elf> = vec![];
-      let mut current_elf = Elf::new();
-
-      for line in lines  {
-        if let Ok(line) = line {
-          let calories = line.parse::<i32>();
-
-          match calories {
-              Ok(cal) => current_elf.push(cal),
-              Err(_) => {
-                elves.push(current_elf);
-                current_elf = Elf::new();
-              },
-          }
-        }            
-      }
-
-      elves.sort();
-      elves.reverse();
-      let max_elves = elves.iter().take(3);
-      let max_of_3: i32 = max_elves.map(|e| e.total()).sum();
-      println!("{:?}", max_of_3);
-      assert_eq!(206582, max_of_3);
-    
-  }
-}
\ No newline at end of file
+    Ok(inventory)
+  }
+
+  fn part1(inventory: &Self::Input) -> i32 {
+    inventory.top_k(1).iter().sum()
+  }
+
+  fn part2(inventory: &Self::Input) -> i32 {
+    inventory.top_k(3).iter().sum()
+  }
+}
+
+// every total sorted, the way part 2 was first solved
+fn sorted_top(inventory: &Inventory, k: usize) -> i32 {
+  let mut totals = inventory.totals().to_vec();
+
+  totals.sort();
+  totals.reverse();
+  totals.iter().take(k).sum()
+}
+
+// the text grouped again on its own, a blank line ending each elf, the way part 1 was first solved;
+// a repeated blank line makes an empty group, skipped rather than ending the batches
+fn batched_top(text: &str, k: usize) -> i32 {
+  text
+    .lines()
+    .map(|line| line.parse::<i32>().ok())
+    .peekable()
+    .batching(|lines| lines.peek().is_some().then(|| lines.map_while(|calories| calories).sum1::<i32>()))
+    .flatten()
+    .map(Reverse)
+    .k_smallest(k)
+    .map(|total| total.0)
+    .sum()
+}
+
+impl Variants for Day01 {
+  const PART1: &'static [Implementation<Inventory, i32>] =
+    &[("top_k", Day01::part1), ("max", |inventory| inventory.totals().iter().copied().max().unwrap_or_default())];
+  const PART2: &'static [Implementation<Inventory, i32>] = &[("top_k", Day01::part2), ("sort", |inventory| sorted_top(inventory, 3))];
+  const TEXT_PART1: &'static [FromText<i32>] = &[("batching", |text| batched_top(text, 1))];
+  const TEXT_PART2: &'static [FromText<i32>] = &[("batching", |text| batched_top(text, 3))];
+}
+
+impl Generate for Day01 {
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let elves: Vec<String> = (0..size)
+      .map(|_| (0..1 + rng.below(6)).map(|_| format!("{}\n", rng.range(1000..=60000))).collect())
+      .collect();
+
+    elves.join("\n")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::{BufReader, Cursor};
+
+  use itertools::Itertools;
+
+  use crate::input;
+
+  use super::*;
+
+  const EXAMPLE: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+
+  #[test]
+  fn adventofcode1() -> Result<()> {
+    let elves = Day01::parse(&input::real(1)?)?;
+    assert_eq!(70116, Day01::part1(&elves));
+
+    Ok(())
+  }
+
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let elves = Day01::parse(&input::real(1)?)?;
+    assert_eq!(206582, Day01::part2(&elves));
+
+    Ok(())
+  }
+
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day01>(50, 1..=8, |text, inventory, &max, &top3| {
+      // the last elf has no blank line after it
+      let mut totals: Vec<i32> = text.split("\n\n").map(|elf| elf.lines().map(|l| l.parse::<i32>().unwrap()).sum()).collect();
+      totals.sort_unstable_by(|a, b| b.cmp(a));
+
+      assert_eq!(totals.len(), inventory.len());
+      assert_eq!((totals[0], totals.iter().take(3).sum()), (max, top3));
+      Ok(())
+    })
+  }
+
+  #[test]
+  fn batching_pipeline() {
+    assert_eq!((24000, 45000), (batched_top(EXAMPLE, 1), batched_top(EXAMPLE, 3)));
+    assert_eq!(0, batched_top("", 3));
+    // an empty group doesn't end the batches early, the elves after it still count
+    assert_eq!((4000, 10000), (batched_top("1000\n2000\n\n3000\n\n\n4000", 1), batched_top("1000\n2000\n\n3000\n\n\n4000", 3)));
+    assert_eq!(7000, batched_top("\n\n3000\n\n4000\n\n\n", 2));
+  }
+
+  #[test]
+  fn keeps_the_last_elf() -> Result<()> {
+    let totals = |text: &str| Inventory::read(text.as_bytes()).map(|inventory| inventory.totals().to_vec());
+
+    assert_eq!(vec![3000, 3000, 4000], totals("1000\n2000\n\n3000\n\n\n4000")?);
+    assert_eq!(vec![3000, 3000, 4000], totals("1000\n2000\n\n3000\n\n4000\n\n")?);
+    assert!(totals("")?.is_empty());
+    assert_eq!(r#"line 2: "x""#, totals("12\nx\n").unwrap_err().to_string());
+
+    Ok(())
+  }
+
+  #[test]
+  fn top_k_and_stats() -> Result<()> {
+    // any reader, here one filling its buffer a few bytes at a time
+    let inventory = Inventory::read(BufReader::with_capacity(4, EXAMPLE.as_bytes()))?;
+
+    assert_eq!(&[6000, 4000, 11000, 24000, 10000], inventory.totals());
+    assert_eq!(55000, inventory.total());
+    assert_eq!(vec![24000, 11000, 10000], inventory.top_k(3));
+    assert_eq!(inventory.totals().iter().copied().sorted().rev().collect_vec(), inventory.top_k(10));
+    assert!(inventory.top_k(0).is_empty());
+    assert_eq!(vec![24000, 11000, 10000], top_k(Cursor::new(EXAMPLE), 3)?);
+
+    let stats = inventory.stats().unwrap();
+    assert_eq!((5, 55000, 4000, 24000, 24000), (stats.elves, stats.total, stats.min, stats.max, stats.p90));
+    assert_eq!((11000.0, 10000.0), (stats.mean, stats.median));
+    assert_eq!(
+      "5 elves carrying 55000 calories, 4000 to 24000, mean 11000.0, median 10000.0, 90th percentile 24000",
+      stats.to_string()
+    );
+    assert_eq!([Some(4000), Some(6000), Some(10000), Some(24000)], [0.0, 25.0, 50.0, 100.0].map(|p| inventory.percentile(p)));
+
+    let even = Inventory::read("1\n\n4\n\n2\n\n3\n".as_bytes())?;
+    assert_eq!(2.5, even.stats().unwrap().median);
+    assert_eq!(None, Inventory::default().stats());
+
+    Ok(())
+  }
+}
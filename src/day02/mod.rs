@@ -1,67 +1,109 @@
+//! Day 2: Rock Paper Scissors.
+
+use eyre::Result;
+
+use crate::{generate::{Generate, Rng}, parse::{self, OrExpected}, solution::Solution};
+
+pub struct Day02;
+
+impl Solution for Day02 {
+  type Input = Vec<(char, char)>;
+  type Answer1 = u64;
+  type Answer2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    parse::each_line(input, |line| {
+      let (opponent, me) = line.split_once(' ').or_expected("`<A|B|C> <X|Y|Z>`")?;
+
+      match (opponent.as_bytes(), me.as_bytes()) {
+        (&[opponent @ b'A'..=b'C'], &[me @ b'X'..=b'Z']) => Ok((opponent as char, me as char)),
+        _ => Err(parse::expected("`<A|B|C> <X|Y|Z>`")),
+      }
+    })
+  }
+
+  fn part1(games: &Self::Input) -> u64 {
+    games.iter().copied().map(points1).sum()
+  }
+
+  fn part2(games: &Self::Input) -> u64 {
+    games.iter().copied().map(points2).sum()
+  }
+}
+
+fn points1(game: (char, char)) -> u64{
+  match game {
+    ('A', 'X') => 1 + 3,
+    ('B', 'Y') => 2 + 3,
+    ('C', 'Z') => 3 + 3,
+    ('A', 'Y') => 2 + 6,
+    ('B', 'Z') => 3 + 6,
+    ('C', 'X') => 1 + 6,
+    ('A', 'Z') => 3,
+    ('B', 'X') => 1,
+    ('C', 'Y') => 2,
+    _ => unreachable!()
+  }
+}
+
+fn points2(game: (char, char)) -> u64{
+  match game {
+    ('A', 'X') => 3,
+    ('B', 'X') => 1,
+    ('C', 'X') => 2,
+
+    ('A', 'Y') => 1 + 3,
+    ('B', 'Y') => 2 + 3,
+    ('C', 'Y') => 3 + 3,
+
+    ('A', 'Z') => 2 + 6,
+    ('B', 'Z') => 3 + 6,
+    ('C', 'Z') => 1 + 6,
+    _ => unreachable!()
+  }
+}
+
+impl Generate for Day02 {
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size * 10).map(|_| format!("{} {}\n", rng.word("ABC", 1), rng.word("XYZ", 1))).collect()
+  }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{io::{BufRead, BufReader}, fs::File, cmp::Reverse};
-    use itertools::Itertools;
+  use crate::input;
 
+  use super::*;
 
   #[test]
-  fn adventofcode1() {
-    let t: u64 = include_str!("input.txt")
-      .lines()
-      .map(|s| {
-        let mut it = s.split(' ');
-        return (it.next().unwrap(), it.next().unwrap())
-      })
-      .map(|g| points1(g))
-      .sum();
-
-    println!("{:?}", t);
-  }
+  fn adventofcode1() -> Result<()> {
+    let games = Day02::parse(&input::real(2)?)?;
+    assert_eq!(10624, Day02::part1(&games));
 
-  fn points1(game: (&str, &str)) -> u64{
-    match game {
-      ("A", "X") => 1 + 3,
-      ("B", "Y") => 2 + 3,
-      ("C", "Z") => 3 + 3,
-      ("A", "Y") => 2 + 6,
-      ("B", "Z") => 3 + 6,
-      ("C", "X") => 1 + 6,
-      ("A", "Z") => 3 + 0,
-      ("B", "X") => 1 + 0,
-      ("C", "Y") => 2 + 0,
-      _ => unreachable!()
-    }
+    Ok(())
   }
 
   #[test]
-  fn adventofcode2() {
-    let t: u64 = include_str!("input.txt")
-      .lines()
-      .map(|s| {
-        let mut it = s.split(' ');
-        return (it.next().unwrap(), it.next().unwrap())
-      })
-      .map(|g| points2(g))
-      .sum();
-
-    println!("{:?}", t);
-  }
+  fn adventofcode2() -> Result<()> {
+    let games = Day02::parse(&input::real(2)?)?;
+    assert_eq!(14060, Day02::part2(&games));
 
-  fn points2(game: (&str, &str)) -> u64{
-    match game {
-      ("A", "X") => 3 + 0,
-      ("B", "X") => 1 + 0,
-      ("C", "X") => 2 + 0,
-
-      ("A", "Y") => 1 + 3,
-      ("B", "Y") => 2 + 3,
-      ("C", "Y") => 3 + 3,
-
-      ("A", "Z") => 2 + 6,
-      ("B", "Z") => 3 + 6,
-      ("C", "Z") => 1 + 6,
-      _ => unreachable!()
-    }
+    Ok(())
   }
 
-}
\ No newline at end of file
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    // shapes and outcomes as 0, 1 or 2: rock, paper, scissors and lose, draw, win
+    crate::generate::check::<Day02>(50, 1..=5, |_, games, &score1, &score2| {
+      let (mut expected1, mut expected2) = (0, 0);
+      for &(opponent, me) in games {
+        let (opponent, me) = ((opponent as u8 - b'A') as i8, (me as u8 - b'X') as i8);
+        expected1 += (me + 1 + 3 * (me - opponent + 1).rem_euclid(3)) as u64;
+        expected2 += ((opponent + me - 1).rem_euclid(3) + 1 + 3 * me) as u64;
+      }
+
+      assert_eq!((expected1, expected2), (score1, score2));
+      Ok(())
+    })
+  }
+}
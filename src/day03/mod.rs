@@ -1,55 +1,156 @@
-#[cfg(test)]
-mod tests {
+//! Day 3: Rucksack Reorganization.
 
-  #[test]
-  fn adventofcode1() {
-    let lines = include_str!("input.txt").lines();
+use eyre::Result;
+
+use crate::{generate::{Generate, Rng}, parse, solution::Solution};
+
+fn priority(common: char) -> u64 {
+  let val = match common {
+    'a'..='z' => common as u8 - b'a' + 1,
+    'A'..='Z' => common as u8 - b'A' + 27,
+    _ => unreachable!(),
+  };
+
+  val as u64
+}
+
+// the item type in both halves of a rucksack
+fn common_item(line: &str) -> Option<char> {
+  let (p1, p2) = line.split_at(line.len() / 2);
+
+  p1.chars().find(|&c| p2.contains(c))
+}
+
+// the item type all three rucksacks of a group carry
+fn badge(group: &[String]) -> Option<char> {
+  let [p1, p2, p3] = group else { return None };
+
+  p1.chars().find(|&c| p2.contains(c) && p3.contains(c))
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+  type Input = Vec<String>;
+  type Answer1 = u64;
+  type Answer2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    let lines = parse::each_line(input, |line| {
+      if line.is_empty() || line.len() % 2 != 0 || !line.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(parse::expected("an even number of letters a-z or A-Z"));
+      }
+      if common_item(line).is_none() {
+        return Err(parse::expected("an item type in both halves"));
+      }
+
+      Ok(line.to_owned())
+    })?;
+
+    if lines.len() % 3 != 0 {
+      return Err(parse::expected(format!("groups of three rucksacks, found {} lines", lines.len())));
+    }
+    for (index, group) in lines.chunks(3).enumerate() {
+      if badge(group).is_none() {
+        let number = 3 * index + 1;
+        return Err(parse::expected_at(number, &group[0], "an item type shared with the next two rucksacks"));
+      }
+    }
 
-    let mut res = 0u64;
+    Ok(lines)
+  }
 
-    for line in lines {
-      let vec = line.chars()
-        .collect::<Vec<_>>();
+  fn part1(lines: &Self::Input) -> u64 {
+    lines.iter().filter_map(|line| common_item(line)).map(priority).sum()
+  }
 
-      let (p1, p2) = vec.split_at(vec.len() / 2);
+  fn part2(lines: &Self::Input) -> u64 {
+    lines.chunks(3).filter_map(badge).map(priority).sum()
+  }
+}
 
-      let common = p1.iter().filter(|&c| p2.contains(c)).next().unwrap();
+impl Generate for Day03 {
+  // groups of three rucksacks, each letter but the item in both halves and the badge in its own pool
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+      let mut letters: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+      rng.shuffle(&mut letters);
+      let (badge, rest) = letters.split_first().unwrap();
+      let (items, pools) = rest.split_at(3);
 
-      let val = match common {
-            ('a'..='z') => *common as u8 - b'a' + 1  ,
-            ('A'..='Z') => *common as u8 - b'A' + 27 ,
-            _ => unreachable!(),
-        };
-      
-      println!("{line:?}");
-      println!("{common:?} - {val:?}");
+      for (item, pools) in items.iter().zip(pools.chunks(pools.len() / 3)) {
+        let (left_pool, right_pool) = pools.split_at(pools.len() / 2);
+        let len = 2 + rng.below(2 * size + 10);
+        let mut left: Vec<char> = [*item, *badge].into_iter().chain((2..len).map(|_| *rng.pick(left_pool))).collect();
+        let mut right: Vec<char> = [*item].into_iter().chain((1..len).map(|_| *rng.pick(right_pool))).collect();
+        rng.shuffle(&mut left);
+        rng.shuffle(&mut right);
 
-      res += val as u64;
-      
+        out.extend(left.into_iter().chain(right).chain(['\n']));
+      }
     }
 
-    println!("{res:?}");
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::input;
+
+  use super::*;
+
+  #[test]
+  fn adventofcode1() -> Result<()> {
+    let lines = Day03::parse(&input::real(3)?)?;
+    assert_eq!(8515, Day03::part1(&lines));
+
+    Ok(())
   }
 
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let lines = Day03::parse(&input::real(3)?)?;
+    assert_eq!(2434, Day03::part2(&lines));
+
+    Ok(())
+  }
 
   #[test]
-  fn adventofcode2() {
-    let mut lines = include_str!("input.txt").lines();
+  fn missing_items() {
+    let err = Day03::parse("abcd\n").unwrap_err();
+    assert_eq!("line 1: \"abcd\": expected an item type in both halves", format!("{err:#}"));
 
-    let mut res = 0u64;
+    let err = Day03::parse("aa\nbb\n").unwrap_err();
+    assert_eq!("expected groups of three rucksacks, found 2 lines", err.to_string());
 
-    while let (Some(p1), Some(p2), Some(p3)) = (lines.next(), lines.next(), lines.next()) {
+    let err = Day03::parse("aa\nbb\naa\n").unwrap_err();
+    assert_eq!("line 1: \"aa\": expected an item type shared with the next two rucksacks", format!("{err:#}"));
+  }
 
-      let common = p1.chars().filter(|&c| p2.contains(c) && p3.contains(c)).next().unwrap();
-      let val = match common {
-        ('a'..='z') => common as u8 - b'a' + 1  ,
-        ('A'..='Z') => common as u8 - b'A' + 27 ,
-        _ => unreachable!(),
-      };
-     res += val as u64;
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    use std::collections::HashSet;
 
+    // the one letter every set shares
+    fn common<'a>(sets: impl IntoIterator<Item = &'a str>) -> char {
+      let shared = sets
+        .into_iter()
+        .map(|set| set.chars().collect::<HashSet<_>>())
+        .reduce(|a, b| &a & &b)
+        .unwrap();
+      assert_eq!(1, shared.len(), "{shared:?}");
+
+      shared.into_iter().next().unwrap()
     }
 
-    println!("{res:?}");
+    crate::generate::check::<Day03>(50, 1..=6, |_, lines, &sum1, &sum2| {
+      let halves: u64 = lines.iter().map(|line| priority(common([&line[..line.len() / 2], &line[line.len() / 2..]]))).sum();
+      let badges: u64 = lines.chunks(3).map(|group| priority(common(group.iter().map(String::as_str)))).sum();
+
+      assert_eq!((halves, badges), (sum1, sum2));
+      Ok(())
+    })
   }
 }
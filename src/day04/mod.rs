@@ -1,81 +1,156 @@
+//! Day 4: Camp Cleanup.
 
+use eyre::Result;
 
-mod pairs {
-    use std::ops::{Range, RangeInclusive};
+use crate::{generate::{Generate, Rng}, parse, solution::Solution};
 
+/// The section assignments of two elves sharing the cleanup.
+pub mod pairs {
+    use std::{ops::RangeInclusive, str::FromStr};
+
+    use eyre::{Report, Result};
+
+    use crate::{interval::IntervalSet, parse::{self, OrExpected}};
+
+  /// Two elves, parsed from `<min>-<max>,<min>-<max>`.
   #[derive(Debug)]
   pub struct Pair(Elf, Elf);
 
   impl Pair {
+    pub fn new(first: Elf, second: Elf) -> Self {
+      Pair(first, second)
+    }
 
+    /// Whether one elf's sections contain all of the other's.
     pub fn overlap(&self) -> bool {
       self.0.contains(&self.1) || self.1.contains(&self.0)
     }
 
+    /// Whether the two elves share at least one section.
     pub fn partial_overlap(&self) -> bool {
-      self.0.partial_overlap(&self.1) || self.1.partial_overlap(&self.0)
+      self.0.partial_overlap(&self.1)
     }
   }
 
-  impl From<&str> for Pair {
-    fn from(input: &str) -> Self {
-        let mut vals = input.split(",");
-        let elf1 = vals.next().unwrap();
-        let elf2 = vals.next().unwrap();
-        Pair(elf1.into(), elf2.into())
+  impl FromStr for Pair {
+    type Err = Report;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let (elf1, elf2) = input.split_once(',').or_expected("`<min>-<max>,<min>-<max>`")?;
+        Ok(Pair(elf1.parse()?, elf2.parse()?))
     }
 }
 
+  /// The sections one elf cleans, parsed from `<min>-<max>`.
   #[derive(Debug)]
-  pub struct Elf(RangeInclusive<usize>);
+  pub struct Elf(IntervalSet<usize>);
 
   impl Elf {
+    pub fn new(sections: RangeInclusive<usize>) -> Self {
+      Elf(sections.into())
+    }
+
     pub fn contains(&self, other: &Elf) -> bool {
-      self.0.contains(other.0.start()) && self.0.contains(other.0.end())
+      self.0.is_superset(&other.0)
     }
 
     pub fn partial_overlap(&self, other: &Elf) -> bool {
-      self.0.contains(other.0.start()) || self.0.contains(other.0.end())
+      self.0.overlaps(&other.0)
     }
   }
 
-  impl From<&str> for Elf {
-    fn from(input: &str) -> Self {
-        let mut vals = input.split("-");
-        let min = vals.next().unwrap().parse::<usize>().unwrap();
-        let max = vals.next().unwrap().parse::<usize>().unwrap();
-        Elf(min..=max)
+  impl FromStr for Elf {
+    type Err = Report;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let (min, max) = input.split_once('-').or_expected(format!("a range `<min>-<max>`, found {input:?}"))?;
+        let min: usize = parse::number(min, "a section number")?;
+        let max: usize = parse::number(max, "a section number")?;
+        if min > max {
+          return Err(parse::expected(format!("the range start before its end, found {input:?}")));
+        }
+
+        Ok(Elf::new(min..=max))
     }
 }
 }
 
+use pairs::Pair;
+
+pub struct Day04;
+
+impl Solution for Day04 {
+  type Input = Vec<Pair>;
+  type Answer1 = usize;
+  type Answer2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    parse::each_line(input, str::parse)
+  }
+
+  fn part1(pairs: &Self::Input) -> usize {
+    pairs.iter()
+    .filter(|p| p.overlap())
+    .count()
+  }
+
+  fn part2(pairs: &Self::Input) -> usize {
+    pairs.iter()
+    .filter(|p| p.partial_overlap())
+    .count()
+  }
+}
+
+impl Generate for Day04 {
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let sections = 10 * size as isize;
+    let mut range = || {
+      let start = rng.range(1..=sections);
+      format!("{start}-{}", rng.range(start..=sections))
+    };
+
+    (0..size * 10).map(|_| format!("{},{}\n", range(), range())).collect()
+  }
+}
 
 #[cfg(test)]
 mod tests {
+  use crate::input;
 
-  use std::ops::RangeInclusive;
-
-use super::pairs::Pair;
+  use super::*;
 
   #[test]
-  fn adventofcode1() {
-    let pairs : Vec<Pair> = include_str!("input.txt").lines()
-    .map(|f| f.into())
-    .filter(|p: &Pair| p.overlap())
-    .collect();
+  fn adventofcode1() -> Result<()> {
+    let pairs = Day04::parse(&input::real(4)?)?;
+    assert_eq!(305, Day04::part1(&pairs));
 
-    println!("{:?}", pairs.len());
+    Ok(())
   }
 
   #[test]
-  fn adventofcode2() {
+  fn adventofcode2() -> Result<()> {
+    let pairs = Day04::parse(&input::real(4)?)?;
+    assert_eq!(811, Day04::part2(&pairs));
 
-    let pairs : Vec<Pair> = include_str!("input.txt").lines()
-    .map(|f| f.into())
-    .filter(|p: &Pair| p.partial_overlap())
-    .collect();
+    Ok(())
+  }
 
-    println!("{:?}", pairs.len());
-    
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day04>(50, 1..=6, |text, _, &contained, &overlapping| {
+      // every section of both elves, one by one
+      let pairs: Vec<(Vec<usize>, Vec<usize>)> = text
+        .lines()
+        .map(|line| {
+          let [a, b, c, d]: [usize; 4] = line.split([',', '-']).map(|n| n.parse().unwrap()).collect::<Vec<_>>().try_into().unwrap();
+          ((a..=b).collect(), (c..=d).collect())
+        })
+        .collect();
+
+      let within = |inner: &[usize], outer: &[usize]| inner.iter().all(|s| outer.contains(s));
+      assert_eq!(pairs.iter().filter(|(a, b)| within(a, b) || within(b, a)).count(), contained);
+      assert_eq!(pairs.iter().filter(|(a, b)| a.iter().any(|s| b.contains(s))).count(), overlapping);
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
@@ -1,157 +1,319 @@
-#[cfg(test)]
-mod tests {
+//! Day 5: Supply Stacks.
+
+use eyre::Result;
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  character::complete::{char, line_ending, satisfy, space0, space1},
+  combinator::{map, value, verify},
+  multi::{many1, separated_list1},
+  sequence::{delimited, preceded, terminated, tuple},
+};
+
+use crate::{generate::{Generate, Rng}, log::{debug, trace}, parse::{self, Parsed}, solution::Solution};
+
+#[derive(Debug, Clone)]
+pub struct State {
+  inner: Vec<Vec<char>>
+}
 
+impl State {
 
-  #[derive(Debug)]
-  struct State {
-    inner: Vec<Vec<char>>
-  }
+  pub fn result(&self) -> String{
+    let mut res = String::new();
 
-  impl State {
+    for elems in &self.inner {
+        res.push(*elems.last().expect("checked in parse"));
+    }
 
-    pub fn result(&self) -> String{
-      let mut res = String::new();
+    res
+  }
 
-      for elems in &self.inner {
-          res.push(*elems.last().unwrap());
-      }
+  pub fn moving_by_one(&mut self, action: &Action) {
 
-      res
+    trace!("Moving {} from {} to {}", action.nbr, action.from, action.to);
+    for _ in 0..action.nbr {
+      let t = self.inner[action.from as usize - 1].pop().expect("checked in parse");
+      self.inner[action.to as usize - 1].push(t);
     }
+  }
+
+  pub fn moving(&mut self, action: &Action) {
 
-    pub fn moving_by_one(&mut self, action: &Action) {
+    trace!("Moving {} from {} to {}", action.nbr, action.from, action.to);
+    let i = action.from as usize - 1;
+    let old_size = self.inner[i].len();
+    let new_size = old_size - action.nbr;
+    //println!("{old_size} -> {new_size}");
 
-      println!("Moving {} from {} to {}", action.nbr, action.from, action.to);
-      for i in 0..action.nbr {
-        let t = self.inner[action.from as usize - 1].pop().unwrap();
-        self.inner[action.to as usize - 1].push(t);
+    let mut elems = self.inner[i][new_size..old_size].to_vec();
+    self.inner[i].truncate(new_size);
+    self.inner[action.to as usize - 1].append(&mut elems);
+    //println!("Result {:#?}", self);
+
+  }
+
+  pub fn new(inputs: Vec<Vec<Option<char>>>) -> Self {
+    // trailing empty stacks may be missing from the top rows
+    let nbr_col = inputs.iter().map(Vec::len).max().unwrap_or_default();
+    let mut inner: Vec<Vec<char>> = vec![vec![]; nbr_col];
+
+    let mut inputs = inputs;
+    inputs.reverse();
+
+    for line in inputs {
+      for (index, val) in line.iter().enumerate() {
+        if let Some(c) = val {
+          inner[index].push(*c);
+        }
       }
     }
 
-    pub fn moving(&mut self, action: &Action) {
+    State { inner }
+  }
+}
 
-      println!("Moving {} from {} to {}", action.nbr, action.from, action.to);
-      let i = action.from as usize - 1;
-      let old_size = self.inner[i].len();
-      let new_size = old_size - action.nbr;
-      //println!("{old_size} -> {new_size}");
+#[derive(Debug)]
+pub struct Action {
+  nbr: usize,
+  from: u8,
+  to: u8
+}
 
-      let mut elems = self.inner[i][new_size..old_size].to_vec();
-      self.inner[i].truncate(new_size);
-      self.inner[action.to as usize - 1].append(&mut elems);
-      //println!("Result {:#?}", self);
+// `[A]` for a crate, three spaces where a stack has none
+fn crate_row(input: &str) -> Parsed<'_, Vec<Option<char>>> {
+  let slot = alt((
+    map(delimited(char('['), satisfy(|c| c != '\n'), char(']')), Some),
+    value(None, tag("   ")),
+  ));
 
-    }
+  terminated(separated_list1(char(' '), slot), line_ending)(input)
+}
 
-    pub fn nbr_column(&self) -> usize {
-      self.inner.len()  
-    }
+fn labels<'a>(stacks: usize) -> impl FnMut(&'a str) -> Parsed<'a, ()> {
+  let numbers = verify(preceded(space0, separated_list1(space1, parse::integer)), move |labels: &Vec<usize>| {
+    labels.iter().copied().eq(1..=stacks)
+  });
 
-    pub fn new(inputs: Vec<Vec<Option<char>>>) -> Self {
-      let nbr_col = inputs[0].len();
-      let mut inner: Vec<Vec<char>> = vec![vec![]; nbr_col];
+  parse::named(format!("the stack numbers 1 to {stacks}"), value((), terminated(numbers, space0)))
+}
 
-      let mut inputs = inputs.clone();
-      inputs.reverse();
+fn stack<'a>(stacks: usize) -> impl FnMut(&'a str) -> Parsed<'a, u8> {
+  parse::named(format!("a stack between 1 and {stacks}"), verify(parse::integer, move |stack: &u8| {
+    (1..=stacks).contains(&(*stack as usize))
+  }))
+}
 
-      for line in inputs {
-        for (index, val) in line.iter().enumerate() {
-          if let Some(c) = val {
-            inner[index].push(*c);
-          }
-        }
-      }
+fn action<'a>(stacks: usize) -> impl FnMut(&'a str) -> Parsed<'a, Action> {
+  map(
+    tuple((
+      preceded(parse::literal("move "), parse::integer),
+      preceded(parse::literal(" from "), stack(stacks)),
+      preceded(parse::literal(" to "), stack(stacks)),
+    )),
+    |(nbr, from, to)| Action { nbr, from, to },
+  )
+}
 
-      State { inner }
-    }
-  }
+fn procedure(input: &str) -> Parsed<'_, Procedure> {
+  let (input, rows) = parse::named("rows of crates such as `[A]     [B]`", many1(crate_row))(input)?;
+  let state = State::new(rows);
+  let stacks = state.inner.len();
 
-  #[derive(Debug)]
-  struct Action {
-    nbr: usize,
-    from: u8,
-    to: u8
-  }
+  // the crates are followed by the stack numbers and a blank line
+  let (input, _) = terminated(labels(stacks), line_ending)(input)?;
+  let (input, _) = parse::named("a blank line before the moves", line_ending)(input)?;
+  let (input, actions) = parse::lines(action(stacks))(input)?;
+
+  Ok((input, Procedure { state, actions }))
+}
+
+#[derive(Debug)]
+pub struct Procedure {
+  state: State,
+  actions: Vec<Action>,
+}
 
-  impl From<&str> for Action {
-    fn from(input: &str) -> Self {
-      let mut vals = input.split(' ')
-        .filter_map(|s| s.parse::<u8>().ok());
-      Self {
-        nbr: vals.next().unwrap() as usize,
-        from: vals.next().unwrap(),
-        to: vals.next().unwrap(),
+impl Procedure {
+  // replays the moves on the stack heights, so that no move takes more crates than its stack holds
+  // and every stack ends with a crate on top
+  fn check(&self, input: &str) -> Result<()> {
+    let mut heights: Vec<usize> = self.state.inner.iter().map(Vec::len).collect();
+    // the moves start after the blank line
+    let lines = parse::numbered(input).skip_while(|(_, line)| !line.is_empty()).skip(1);
+
+    for (action, (number, line)) in self.actions.iter().zip(lines) {
+      let (from, to) = (action.from as usize - 1, action.to as usize - 1);
+      if action.nbr > heights[from] {
+        let what = format!("a move of at most {} crate(s) from stack {}", heights[from], action.from);
+        return Err(parse::expected_at(number, line, what));
+      }
+      heights[from] -= action.nbr;
+      heights[to] += action.nbr;
     }
+
+    match heights.iter().position(|&height| height == 0) {
+      Some(empty) => Err(parse::expected(format!("a crate on top of stack {} after the moves", empty + 1))),
+      None => Ok(()),
     }
+  }
 }
 
-  #[derive(Debug, PartialEq, Eq)]
-  enum Possibility {
-    Empty,
-    Present(char),
-    End
+pub struct Day05;
+
+impl Solution for Day05 {
+  type Input = Procedure;
+  type Answer1 = String;
+  type Answer2 = String;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    let procedure = parse::whole(input, procedure)?;
+    procedure.check(input)?;
+
+    Ok(procedure)
   }
 
-  fn valid_line(input: &str) -> Option<Vec<Option<char>>> {
-    let mut res = vec![];
-    let mut input = input.chars();
-    
-    while let Some(v) = match (input.next(), input.next(), input.next(), input.next()) {
-      (Some(' '), Some(' '), Some(' '), _) => Some(Possibility::Empty),
-      (Some('['), Some(c), Some(']'), _) => Some(Possibility::Present(c)),
-      (None, None, None, None) => None,
-      _ => return None,
-  }{
-    match v {
-        Possibility::Empty => res.push(None),
-        Possibility::Present(c) =>res.push(Some(c)),
-        Possibility::End => (),
+  fn part1(procedure: &Self::Input) -> String {
+    let mut state = procedure.state.clone();
+    //println!("{actions:?}");
+    debug!("before {state:?}");
+
+    for action in procedure.actions.iter() {
+      state.moving_by_one(action);
+      //println!("action {action:?}");
+      //println!("current {state:#?}");
     }
-  }
-    Some(res)
-  }
 
-  #[test]
-  fn adventofcode1() {
-    
-    let mut lines = include_str!("input.txt").lines();
+    debug!("after {state:?}");
+
+    state.result()
+  }
 
-    let mut state = State::new(lines.clone().map_while(|l| valid_line(l)).collect());
-    let actions: Vec<Action> = lines.skip(state.nbr_column() + 1).map(|s| s.into()).collect::<Vec<_>>();
+  fn part2(procedure: &Self::Input) -> String {
+    let mut state = procedure.state.clone();
     //println!("{actions:?}");
-    println!("before {state:?}");
+    debug!("before {state:?}");
 
-    for action in actions {
-      state.moving_by_one(&action);
+    for action in procedure.actions.iter() {
+      state.moving(action);
       //println!("action {action:?}");
       //println!("current {state:#?}");
+    }
+
+    debug!("after {state:?}");
+
+    state.result()
+  }
+}
+
+impl Generate for Day05 {
+  // moves never empty a stack, so every stack has a crate on top in the end
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let stacks = 2 + rng.below(size.min(8));
+    let mut heights: Vec<usize> = (0..stacks).map(|_| 1 + rng.below(size + 2)).collect();
+    heights[rng.below(stacks)] += 1;
+
+    let mut out = String::new();
+    let top = *heights.iter().max().unwrap();
+    for row in (0..top).rev() {
+      let mut slots: Vec<String> = heights
+        .iter()
+        .map(|&height| if height > row { format!("[{}]", rng.word("ABCDEFGHIJKLMNOPQRSTUVWXYZ", 1)) } else { "   ".to_owned() })
+        .collect();
+      // top rows may stop at their last crate
+      if rng.one_in(2) {
+        while slots.last().is_some_and(|slot| slot.trim().is_empty()) {
+          slots.pop();
+        }
+      }
+      out += &format!("{}\n", slots.join(" "));
+    }
+    let labels: Vec<String> = (1..=stacks).map(|n| format!(" {n} ")).collect();
+    out += &format!("{}\n\n", labels.join(" "));
+
+    for _ in 0..3 * size {
+      let from = loop {
+        let from = rng.below(stacks);
+        if heights[from] > 1 {
+          break from;
+        }
+      };
+      let to = (from + 1 + rng.below(stacks - 1)) % stacks;
+      let nbr = 1 + rng.below(heights[from] - 1);
+      heights[from] -= nbr;
+      heights[to] += nbr;
+      out += &format!("move {nbr} from {} to {}\n", from + 1, to + 1);
+    }
+
+    out
   }
+}
 
-    println!("after {state:?}");
+#[cfg(test)]
+mod tests {
+  use crate::input;
 
-    println!("{:?}", state.result());
+  use super::*;
+
+  #[test]
+  fn adventofcode1() -> Result<()> {
+    let procedure = Day05::parse(&input::real(5)?)?;
+    assert_eq!("DHBJQJCCW", Day05::part1(&procedure));
 
+    Ok(())
   }
 
   #[test]
-  fn adventofcode2() {
-    
-    let mut lines = include_str!("input.txt").lines();
+  fn adventofcode2() -> Result<()> {
+    let procedure = Day05::parse(&input::real(5)?)?;
+    assert_eq!("WJVRLSJJT", Day05::part2(&procedure));
 
-    let mut state = State::new(lines.clone().map_while(|l| valid_line(l)).collect());
-    let actions: Vec<Action> = lines.skip(state.nbr_column() + 1).map(|s| s.into()).collect::<Vec<_>>();
-    //println!("{actions:?}");
-    println!("before {state:?}");
+    Ok(())
+  }
 
-    for action in actions {
-      state.moving(&action);
-      //println!("action {action:?}");
-      //println!("current {state:#?}");
+  #[test]
+  fn impossible_moves() {
+    let stacks = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n";
+
+    let err = Day05::parse(&format!("{stacks}move 1 from 2 to 1\nmove 5 from 3 to 1\n")).unwrap_err();
+    assert_eq!("line 7: \"move 5 from 3 to 1\": expected a move of at most 1 crate(s) from stack 3", format!("{err:#}"));
+
+    let err = Day05::parse(&format!("{stacks}move 1 from 3 to 1\n")).unwrap_err();
+    assert_eq!("expected a crate on top of stack 3 after the moves", err.to_string());
   }
 
-    println!("after {state:?}");
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day05>(50, 1..=8, |text, _, one_by_one, all_at_once| {
+      let (crates, moves) = text.split_once("\n\n").unwrap();
+      let mut rows: Vec<&str> = crates.lines().collect();
+      let stacks = rows.pop().unwrap().split_whitespace().count();
+
+      // crate letters sit at every fourth column
+      let mut start = vec![vec![]; stacks];
+      for row in rows.iter().rev() {
+        for (stack, c) in row.chars().skip(1).step_by(4).enumerate() {
+          if c != ' ' {
+            start[stack].push(c);
+          }
+        }
+      }
 
-    println!("{:?}", state.result());
+      for (reverse, answer) in [(true, one_by_one), (false, all_at_once)] {
+        let mut stacks = start.clone();
+        for line in moves.lines() {
+          let [nbr, from, to]: [usize; 3] = line.split(' ').filter_map(|w| w.parse().ok()).collect::<Vec<_>>().try_into().unwrap();
+          let at = stacks[from - 1].len() - nbr;
+          let mut moved = stacks[from - 1].split_off(at);
+          if reverse {
+            moved.reverse();
+          }
+          stacks[to - 1].extend(moved);
+        }
 
+        assert_eq!(&stacks.iter().map(|stack| stack.last().unwrap()).collect::<String>(), answer);
+      }
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
@@ -1,75 +1,140 @@
-#[cfg(test)]
-mod tests {
+//! Day 6: Tuning Trouble.
 
-  #[derive(Debug)]
-  struct Signal(String);
+use eyre::Result;
 
-  impl Signal {
-    pub fn new(string: String) -> Self {
-      Signal(string)
-    }
+use crate::{generate::{Generate, Rng}, log::trace, parse, solution::Solution};
 
-    pub fn first_marker(&self, size: usize) -> usize {
-      let mut chars = self.0.chars();
-      let mut index = 0;
-      
-      let mut window = vec![];
-      
-      for _ in 0..size {
-        window.push(chars.next().unwrap());
-        index += 1;
-      }
+#[derive(Debug)]
+pub struct Signal(String);
 
+impl Signal {
+  pub fn new(string: String) -> Self {
+    Signal(string)
+  }
 
-      while duplicate_in(&window) {
-        window.remove(0);
-        window.push(chars.next().unwrap());
-        index += 1;
-      }
+  // where the first `size` different letters in a row end, if there are any
+  pub fn first_marker(&self, size: usize) -> Option<usize> {
+    let chars: Vec<char> = self.0.chars().collect();
 
-      return index;
+    chars
+      .windows(size)
+      .position(|window| !duplicate_in(window))
+      .map(|index| index + size)
+  }
 
-    }
+  fn marker(&self, size: usize) -> Result<usize> {
+    self.first_marker(size).ok_or_else(|| parse::expected_at(1, &self.0, format!("{size} different letters in a row")))
   }
+}
 
-  fn duplicate_in(window: &Vec<char>) -> bool {
-    println!("looking in {window:?}");
-    for i in 0..window.len() - 1 {
-      for j in i+1..window.len() {
-        println!("comparing {i} - {j}");
-        if window[i] == window[j] {
-          println!("Duplicate found");
-          return true;
-        }
+fn duplicate_in(window: &[char]) -> bool {
+  trace!("looking in {window:?}");
+  for i in 0..window.len() - 1 {
+    for j in i+1..window.len() {
+      trace!("comparing {i} - {j}");
+      if window[i] == window[j] {
+        trace!("Duplicate found");
+        return true;
       }
     }
-    println!("No duplicate");
-    return false;
+  }
+  trace!("No duplicate");
+  false
+}
+
+const START_OF_PACKET: usize = 4;
+const START_OF_MESSAGE: usize = 14;
+
+pub struct Day06;
+
+impl Solution for Day06 {
+  type Input = Signal;
+  type Answer1 = Result<usize>;
+  type Answer2 = Result<usize>;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    let mut lines = parse::numbered(input);
+    let (_, signal) = lines.next().unwrap_or_default();
+    if signal.is_empty() || !signal.chars().all(|c| c.is_ascii_lowercase()) {
+      return Err(parse::expected_at(1, signal, "a datastream of letters a-z"));
+    }
+    if let Some((number, line)) = lines.next() {
+      return Err(parse::expected_at(number, line, "a single line"));
+    }
+
+    Ok(Signal::new(signal.to_owned()))
+  }
+
+  fn part1(signal: &Self::Input) -> Result<usize> {
+    signal.marker(START_OF_PACKET)
+  }
+
+  fn part2(signal: &Self::Input) -> Result<usize> {
+    signal.marker(START_OF_MESSAGE)
+  }
+}
+
+impl Generate for Day06 {
+  // few letters repeat often, with fourteen different ones somewhere to end part 2
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let letters = 2 + rng.below(12);
+    let mut signal = rng.word(&"abcdefghijklmnopqrstuvwxyz"[..letters], 20 * size);
+
+    let mut marker: Vec<char> = ('a'..='z').collect();
+    rng.shuffle(&mut marker);
+    let at = rng.below(signal.len());
+    signal.insert_str(at, &marker[..14].iter().collect::<String>());
+
+    format!("{signal}\n")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::input;
+
+  use super::*;
+
+  #[test]
+  fn adventofcode1() -> Result<()> {
+    let signal = Day06::parse(&input::real(6)?)?;
+    assert_eq!(1480, Day06::part1(&signal)?);
+
+    Ok(())
+  }
+
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let signal = Day06::parse(&input::real(6)?)?;
+    assert_eq!(2746, Day06::part2(&signal)?);
+
+    Ok(())
   }
 
   #[test]
-  fn adventofcode1() {
-    
-    let streams: Vec<_> = include_str!("input.txt")
-      .lines()
-      .map(|s| Signal::new(s.to_owned()))
-      .map(|s| s.first_marker(4))
-      .collect();
+  fn no_marker() -> Result<()> {
+    let signal = Day06::parse("abc\n")?;
+    let err = Day06::part1(&signal).unwrap_err();
+    assert_eq!("line 1: \"abc\": expected 4 different letters in a row", format!("{err:#}"));
 
-    println!("{:?}", streams);
+    let signal = Day06::parse("abcdabcd\n")?;
+    assert_eq!(4, Day06::part1(&signal)?);
+    let err = Day06::part2(&signal).unwrap_err();
+    assert_eq!("line 1: \"abcdabcd\": expected 14 different letters in a row", format!("{err:#}"));
 
+    Ok(())
   }
 
   #[test]
-  fn adventofcode2() {
-    
-    let streams: Vec<_> = include_str!("input.txt")
-      .lines()
-      .map(|s| Signal::new(s.to_owned()))
-      .map(|s| s.first_marker(14))
-      .collect();
+  fn generated_inputs() -> Result<()> {
+    use std::collections::HashSet;
 
-    println!("{:?}", streams);
+    crate::generate::check::<Day06>(50, 1..=10, |text, _, start, message| {
+      let chars: Vec<char> = text.trim_end().chars().collect();
+      let marker = |size| chars.windows(size).position(|w| w.iter().collect::<HashSet<_>>().len() == size).unwrap() + size;
 
+      assert_eq!((marker(4), marker(14)), (*start.as_ref().unwrap(), *message.as_ref().unwrap()));
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
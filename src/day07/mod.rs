@@ -1,11 +1,13 @@
+//! Day 7: No Space Left On Device.
+
 use std::{str::FromStr, collections::HashMap};
-use eyre::{Result, Context, eyre};
+use eyre::{eyre, Result, WrapErr};
+
+use crate::{generate::{Generate, Rng}, parse::{self, OrExpected}, solution::Solution};
 
 use nom::{
   IResult,
-  bytes::complete::{tag, take_while_m_n, take},
-  combinator::map_res,
-  sequence::tuple, error::ParseError
+  bytes::complete::{tag, take},
 };
 
 #[derive(Debug, Eq, PartialEq)]
@@ -23,7 +25,7 @@ enum LsResult {
 
 
 #[derive(PartialEq, Eq, Debug)]
-enum Entity {
+pub enum Entity {
   File(usize),
   Directory(Map)
 }
@@ -33,12 +35,12 @@ impl Entity {
   fn get_size(&self) -> usize {
     match self {
         Entity::File(size) => *size,
-        Entity::Directory(map) => map.iter().map(|e| e.1.get_size()).sum(),
+        Entity::Directory(map) => map.values().map(|e| e.get_size()).sum(),
     }
   }
 }
 
-type Map = HashMap<String, Entity>;
+pub type Map = HashMap<String, Entity>;
 
 impl FromStr for Command {
     type Err = eyre::Report;
@@ -46,147 +48,222 @@ impl FromStr for Command {
     fn from_str(input: &str) -> Result<Self> {
       let res: IResult<&str, &str> = tag("$ ")(input);
       if res.is_ok() {
-        let (left, _) = res.map_err(|_| eyre!("Not starting with $"))?;
+        let (left, _) = res.map_err(|_| parse::expected("`$ `"))?;
       
         let res: IResult<&str, &str> = take(2usize)(left);
-        let (left, found) = res.map_err(|_| eyre!("not followed by command"))?;
+        let (left, found) = res.map_err(|_| parse::expected("`$ cd <dir>` or `$ ls`"))?;
         
-        match found {
-          "ls" => return Ok(Command::Ls),
-          "cd" => {
-            let path = left.trim();
-            return Ok(Command::Cd(path.to_owned()));
-          },
-          _ => unreachable!()
+        match (found, left.trim()) {
+          ("ls", "") => Ok(Command::Ls),
+          ("cd", path) if left.starts_with(' ') && !path.is_empty() => Ok(Command::Cd(path.to_owned())),
+          _ => Err(parse::expected("`$ cd <dir>` or `$ ls`")),
         }
       }else {
-        let splits: Vec<&str> = input.splitn(2, " ").collect();
-        let p1 = splits[0];
-        let p2 = splits[1];
+        let (p1, p2) = input.split_once(' ').or_expected("`dir <name>` or `<size> <name>`")?;
 
         match (p1, p2) {
-          ("dir", _) => return Ok(Command::LsResult(LsResult::Directory(p2.to_owned()))),
-          _ => return Ok(Command::LsResult(LsResult::File(p2.to_owned(), p1.parse::<usize>().unwrap())))
+          ("dir", _) => Ok(Command::LsResult(LsResult::Directory(p2.to_owned()))),
+          _ => Ok(Command::LsResult(LsResult::File(p2.to_owned(), parse::number(p1, "a file size or `dir`")?)))
         }
       }
-      unreachable!()
     }
 }
 
 
 
-#[cfg(test)]
-mod tests {
-    use std::str::Lines;
-
-    use super::*;
+fn flatten<'a>(state: &'a Map, acc:&mut Vec<&'a Entity> ) {
 
+  for val in state.values() {
+    acc.push(val);
+  }
 
-  #[test]
-  fn adventofcode1() -> Result<()> {
+  for val in state.values() {
+    if let Entity::Directory(val) = val {
+      flatten(val, acc);
+    }
+  }
 
-    let mut inputs = include_str!("input.txt").lines();
+}
 
-    let mut state: Map = HashMap::new();
-    apply_to_state(&mut state, &mut inputs);
+fn apply_to_state<'a>(state: &mut Map, lines: &mut impl Iterator<Item = (usize, &'a str)>) -> Result<()> {
+
+  while let Some((number, line)) = lines.next() {
+    let input = Command::from_str(line).wrap_err_with(|| parse::at_line(number, line))?;
+    match input {
+      Command::LsResult(e) => {
+        match e {
+          LsResult::File(n, s) => state.insert(n.to_owned(), Entity::File(s)),
+          LsResult::Directory(n) => state.insert(n.to_owned(), Entity::Directory(HashMap::new())),
+        };
+      },
+      Command::Cd(place) => {
+        match place.as_str() {
+          ".." => return Ok(()),
+          other => {
+            if !state.contains_key(other) {
+              state.insert(other.to_owned(), Entity::Directory(HashMap::new()));
+            }
+            match state.get_mut(other) {
+              Some(Entity::Directory(el)) => apply_to_state(el, lines)?,
+              _ => return Err(parse::expected_at(number, line, format!("{other:?} to be a directory"))),
+            }
+          },
+        }
+      },
+      Command::Ls => (),
+  };
+  }
 
-    let mut all_directories: Vec<&Entity> = vec![];
-    flatten(&state, &mut all_directories);
+  Ok(())
+}
 
-    let total_size_folder: usize = all_directories
-      .into_iter()
-      .filter(|&e| match e {
-        Entity::File(_) => false,
-        Entity::Directory(_) => true,
-      })
-      .map(|el| el.get_size())
-      .filter(|s| *s <= 100000usize )
-      .sum();
+fn directory_sizes(state: &Map) -> impl Iterator<Item = usize> + '_ {
+  let mut all_directories: Vec<&Entity> = vec![];
+  flatten(state, &mut all_directories);
+
+  all_directories
+    .into_iter()
+    .filter(|&e| match e {
+      Entity::File(_) => false,
+      Entity::Directory(_) => true,
+    })
+    .map(|el| el.get_size())
+}
 
-    dbg!(total_size_folder);
+const DISK_SPACE: usize = 70000000;
+const NEEDED_SPACE: usize = 30000000;
+
+// the size of the smallest directory that leaves enough free space once deleted
+fn directory_to_delete(state: &Map) -> Result<usize> {
+  let used = state.values().map(|e| e.get_size()).sum::<usize>();
+  let free_space = DISK_SPACE
+    .checked_sub(used)
+    .ok_or_else(|| parse::expected(format!("at most {DISK_SPACE} used on the disk, found {used}")))?;
+  let space_to_free = NEEDED_SPACE.saturating_sub(free_space);
+
+  directory_sizes(state)
+    .filter(|s| *s >= space_to_free )
+    .min()
+    .ok_or_else(|| eyre!("no directory frees enough space, {space_to_free} more are needed"))
+}
 
-    Ok(())
-  }
+pub struct Day07;
 
-  #[test]
-  fn adventofcode2() -> Result<()> {
+impl Solution for Day07 {
+  type Input = Map;
+  type Answer1 = usize;
+  type Answer2 = Result<usize>;
 
-    let mut inputs = include_str!("input.txt").lines();
+  fn parse(input: &str) -> Result<Self::Input> {
+    let mut inputs = parse::numbered(input);
+    let first = input.lines().next().unwrap_or_default();
+    if first != "$ cd /" {
+      return Err(parse::expected_at(1, first, "`$ cd /` to start from the root"));
+    }
 
     let mut state: Map = HashMap::new();
-    apply_to_state(&mut state, &mut inputs);
+    apply_to_state(&mut state, &mut inputs)?;
 
-    let current_used_space = state.iter().next().unwrap().1.get_size();
-    dbg!(current_used_space);
+    Ok(state)
+  }
 
-    let free_space = 70000000 - current_used_space;
-    let space_to_free = 30000000 - free_space;
+  fn part1(state: &Self::Input) -> usize {
+    directory_sizes(state)
+      .filter(|s| *s <= 100000usize )
+      .sum()
+  }
 
-    let mut all_directories: Vec<&Entity> = vec![];
-    flatten(&state, &mut all_directories);
+  fn part2(state: &Self::Input) -> Result<usize> {
+    directory_to_delete(state)
+  }
+}
 
-    let size_of_folder_to_delete: usize = all_directories
-      .into_iter()
-      .filter(|&e| match e {
-        Entity::File(_) => false,
-        Entity::Directory(_) => true,
-      })
-      .map(|el| el.get_size())
-      .filter(|s| *s >= space_to_free )
-      .min()
-      .unwrap();
+// a directory of the generated file system, drawn as the shell session exploring it
+#[derive(Default)]
+struct Tree {
+  files: Vec<(String, usize)>,
+  dirs: Vec<(String, Tree)>,
+}
 
-    dbg!(size_of_folder_to_delete);
+impl Tree {
+  fn random(rng: &mut Rng, depth: usize, size: usize) -> Self {
+    let mut tree = Tree::default();
+    let mut names: Vec<String> = (0..2 + rng.below(size + 2))
+      .map(|i| {
+        let len = 1 + rng.below(4);
+        format!("{}{i}", rng.word("abcdefghij", len))
+      })
+      .collect();
+    rng.shuffle(&mut names);
+
+    for name in names {
+      if depth > 0 && rng.one_in(3) {
+        tree.dirs.push((name, Tree::random(rng, depth - 1, size)));
+      } else {
+        let ext = if rng.one_in(2) { "" } else { ".txt" };
+        tree.files.push((format!("{name}{ext}"), rng.range(1000..=300_000) as usize));
+      }
+    }
 
-    Ok(())
+    tree
   }
 
-  fn flatten<'a>(state: &'a Map, acc:&mut Vec<&'a Entity> ) {
+  fn size(&self) -> usize {
+    self.files.iter().map(|(_, size)| size).sum::<usize>() + self.dirs.iter().map(|(_, dir)| dir.size()).sum::<usize>()
+  }
 
-    for (_, val) in state {
-      acc.push(val);
+  fn session(&self, name: &str, out: &mut String) {
+    *out += &format!("$ cd {name}\n$ ls\n");
+    for (name, _) in &self.dirs {
+      *out += &format!("dir {name}\n");
     }
-
-    for (_, val) in state {
-      if let Entity::Directory(val) = val {
-        flatten(val, acc);
-      }
+    for (name, size) in &self.files {
+      *out += &format!("{size} {name}\n");
+    }
+    for (name, dir) in &self.dirs {
+      dir.session(name, out);
     }
+    *out += "$ cd ..\n";
+  }
+}
 
+impl Generate for Day07 {
+  // more than the 40000000 used that part 2 needs, and less than the 70000000 of the disk
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut root = Tree::random(rng, 1 + size.min(6) / 2, size);
+    let target = rng.range(41_000_000..=69_000_000) as usize;
+    root.files.push(("big.dat".to_owned(), target.saturating_sub(root.size()).max(1)));
+
+    let mut out = String::new();
+    root.session("/", &mut out);
+    // nothing comes after leaving the root
+    out.truncate(out.len() - "$ cd ..\n".len());
+
+    out
   }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input;
+
+    use super::*;
 
-  fn apply_to_state<'a>(state: &'a mut Map, lines: &mut Lines)-> () {
-
-    while let Some(input) = lines.next() {
-      let input = Command::from_str(input).unwrap();
-      match input {
-        Command::LsResult(e) => {
-          match e {
-            LsResult::File(n, s) => state.insert(n.to_owned(), Entity::File(s)),
-            LsResult::Directory(n) => state.insert(n.to_owned(), Entity::Directory(HashMap::new())),
-          };
-        },
-        Command::Cd(place) => {
-          match place.as_str() {
-            ".." => return (),
-            other => {
-              if !state.contains_key(other) {
-                state.insert(other.to_owned(), Entity::Directory(HashMap::new()));
-              }
-              let new_state = state.get_mut(&other.to_owned()).unwrap();
-              if let Entity::Directory(el) = new_state {
-                apply_to_state(el, lines);
-              }
-            },
-          }
-        },
-        Command::Ls => (),
-    };
-    }
-    
-    
 
-    return ();
+  #[test]
+  fn adventofcode1() -> Result<()> {
+    let state = Day07::parse(&input::real(7)?)?;
+    assert_eq!(1648397, Day07::part1(&state));
+
+    Ok(())
+  }
+
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let state = Day07::parse(&input::real(7)?)?;
+    assert_eq!(1815525, Day07::part2(&state)?);
+
+    Ok(())
   }
 
 
@@ -203,4 +280,44 @@ mod tests {
 
   }
 
+  #[test]
+  fn invalid_sessions() -> Result<()> {
+    let err = Day07::parse("$ ls\n").unwrap_err();
+    assert_eq!("line 1: \"$ ls\": expected `$ cd /` to start from the root", format!("{err:#}"));
+
+    let state = Day07::parse("$ cd /\n$ ls\n80000000 a\n")?;
+    assert_eq!(0, Day07::part1(&state));
+    let err = Day07::part2(&state).unwrap_err();
+    assert_eq!("expected at most 70000000 used on the disk, found 80000000", err.to_string());
+
+    Ok(())
+  }
+
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day07>(30, 1..=6, |text, _, &small, freed| {
+      // every file counts towards each directory on the way to it
+      let mut path: Vec<usize> = vec![];
+      let mut sizes = vec![];
+      for line in text.lines() {
+        match line.split(' ').collect::<Vec<_>>()[..] {
+          ["$", "cd", ".."] => {
+            path.pop();
+          }
+          ["$", "cd", _] => {
+            sizes.push(0);
+            path.push(sizes.len() - 1);
+          }
+          [size, _] if size != "$" && size != "dir" => path.iter().for_each(|&dir| sizes[dir] += size.parse::<usize>().unwrap()),
+          _ => (),
+        }
+      }
+
+      let needed = sizes[0] - 40_000_000;
+      assert_eq!(sizes.iter().filter(|&&size| size <= 100_000).sum::<usize>(), small);
+      assert_eq!(sizes.iter().filter(|&&size| size >= needed).min(), freed.as_ref().ok());
+      Ok(())
+    })
+  }
+
 }
\ No newline at end of file
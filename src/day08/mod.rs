@@ -1,62 +1,44 @@
+//! Day 8: Treetop Tree House.
+
+use eyre::Result;
+
+use crate::{generate::{Generate, Rng}, grid::Grid, parse, point::Vec2, solution::Solution};
+
 trait Visible {
-    fn is_visible(&self, coord: (usize, usize)) -> bool;
-    fn compute_visibility(&self, coord: (usize, usize)) -> usize;
+    fn lines_of_sight(&self, pos: Vec2) -> [Vec<u8>; 4];
+    fn is_visible(&self, pos: Vec2) -> bool;
+    fn compute_visibility(&self, pos: Vec2) -> usize;
 }
 
-impl Visible for Vec<Vec<u8>> {
-    fn is_visible(&self, coord: (usize, usize)) -> bool {
-        let north_three: Vec<u8> = (0..coord.0).map(|s| self[s][coord.1]).collect();
-        let south_three: Vec<u8> = (coord.0 + 1..self.len())
-            .map(|s| self[s][coord.1])
-            .collect();
-        let east_three: Vec<u8> = (coord.1 + 1..self.len())
-            .map(|s| self[coord.0][s])
-            .collect();
-        let west_three: Vec<u8> = (0..coord.1).map(|s| self[coord.0][s]).collect();
-
-        //println!("{coord:?}");
-        //println!("north : {north_three:?}");
-        //println!("south : {south_three:?}");
-        //println!("west : {west_three:?}");
-        //println!("east : {east_three:?}");
-
-        let curr = self[coord.0][coord.1];
-        //println!("curr : {curr:?}");
-
-        north_three.iter().all(|v| v < &curr)
-            || south_three.iter().all(|v| v < &curr)
-            || east_three.iter().all(|v| v < &curr)
-            || west_three.iter().all(|v| v < &curr)
+impl Visible for Grid<u8> {
+    // trees between `pos` and each edge, nearest first
+    fn lines_of_sight(&self, pos: Vec2) -> [Vec<u8>; 4] {
+        let column: Vec<u8> = self.column(pos.x).copied().collect();
+        let row: Vec<u8> = self.row(pos.y).copied().collect();
+        let (x, y) = (pos.x as usize, pos.y as usize);
+
+        [
+            column[..y].iter().rev().copied().collect(),
+            column[y + 1..].to_vec(),
+            row[..x].iter().rev().copied().collect(),
+            row[x + 1..].to_vec(),
+        ]
     }
 
-    fn compute_visibility(&self, coord: (usize, usize)) -> usize {
-        let north_three: Vec<u8> = (0..coord.0).map(|s| self[s][coord.1]).rev().collect();
-        let south_three: Vec<u8> = (coord.0 + 1..self.len())
-            .map(|s| self[s][coord.1])
-            .collect();
-        let east_three: Vec<u8> = (coord.1 + 1..self.len())
-            .map(|s| self[coord.0][s])
-            .collect();
-        let west_three: Vec<u8> = (0..coord.1).map(|s| self[coord.0][s]).rev().collect();
-
-        let curr = self[coord.0][coord.1];
-        // println!("{coord:?}");
-        // println!("curr : {curr:?}");
-        // println!("north : {north_three:?}");
-        // println!("south : {south_three:?}");
-        // println!("west : {west_three:?}");
-        // println!("east : {east_three:?}");
-
-        let val = visibility_check(&north_three, curr) *
-          visibility_check(&south_three, curr) *
-          visibility_check(&west_three, curr) *
-          visibility_check(&east_three, curr);
-        // println!("found : {val}");
-        val
+    fn is_visible(&self, pos: Vec2) -> bool {
+        let curr = self[pos];
+
+        self.lines_of_sight(pos).iter().any(|trees| trees.iter().all(|&v| v < curr))
+    }
+
+    fn compute_visibility(&self, pos: Vec2) -> usize {
+        let curr = self[pos];
+
+        self.lines_of_sight(pos).iter().map(|trees| visibility_check(trees, curr)).product()
     }
 }
 
-fn visibility_check(trees: &Vec<u8>, curr: u8) -> usize {
+fn visibility_check(trees: &[u8], curr: u8) -> usize {
   let mut view_ok = true;
     trees
         .iter()
@@ -70,82 +52,89 @@ fn visibility_check(trees: &Vec<u8>, curr: u8) -> usize {
           }
           true
         })
-        .collect::<Vec<_>>()
-        .len()
+        .count()
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
+pub struct Day08;
 
-    use crate::day08::Visible;
+impl Solution for Day08 {
+    type Input = Grid<u8>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    #[test]
-    fn adventofcode1() {
-        let input = include_str!("input.txt");
+    fn parse(input: &str) -> Result<Self::Input> {
+        Grid::parse(input, |c| {
+            let height = c.to_digit(10).ok_or_else(|| parse::expected(format!("a tree height 0-9, found {c:?}")))?;
+            Ok(height as u8)
+        })
+    }
 
-        let mut data: Vec<Vec<u8>> = vec![];
+    fn part1(trees: &Self::Input) -> usize {
+        // trees on the edge see out over nothing, so they count too
+        trees.positions().filter(|&pos| trees.is_visible(pos)).count()
+    }
 
-        input.lines().for_each(|s| {
-            let vec = s.chars().map(|c| c.to_digit(10).unwrap() as u8).collect();
-            data.push(vec);
-        });
+    fn part2(trees: &Self::Input) -> usize {
+        trees.positions().map(|pos| trees.compute_visibility(pos)).max().unwrap_or_default()
+    }
+}
+
+impl Generate for Day08 {
+    // rarely square, down to a single row or column
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let (width, height) = (1 + rng.below(2 * size), 1 + rng.below(2 * size));
+
+        (0..height).map(|_| rng.word("0123456789", width) + "\n").collect()
+    }
+}
 
-        let mut set: HashSet<(usize, usize)> = HashSet::new();
+#[cfg(test)]
+mod tests {
+    use crate::input;
 
-        let y_size = data.len();
-        let x_size = data[0].len();
+    use super::*;
 
-        for i in 0..x_size {
-            set.insert((i, 0));
-            set.insert((i, y_size - 1));
-        }
+    #[test]
+    fn adventofcode1() -> Result<()> {
+        let data = Day08::parse(&input::real(8)?)?;
+        assert_eq!(1560, Day08::part1(&data));
 
-        for i in 0..y_size {
-            set.insert((0, i));
-            set.insert((x_size - 1, i));
-        }
+        Ok(())
+    }
 
-        for i in 1..x_size - 1 {
-            for j in 1..y_size - 1 {
-                if data.is_visible((i, j)) {
-                    set.insert((i, j));
-                }
-            }
-        }
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let data = Day08::parse(&input::real(8)?)?;
+        assert_eq!(252000, Day08::part2(&data));
 
-        //println!("{:?}", data);
-        println!("{:?}", set.len());
+        Ok(())
     }
 
     #[test]
-    fn adventofcode2() {
-        let input = include_str!("input.txt");
-
-        let mut data: Vec<Vec<u8>> = vec![];
-
-        input.lines().for_each(|s| {
-            let vec = s.chars().map(|c| c.to_digit(10).unwrap() as u8).collect();
-            data.push(vec);
-        });
-
-        let mut max = 0;
-        let mut current_coord = (0,0);
-        let y_size = data.len();
-        let x_size = data[0].len();
-
-        for i in 1..x_size -1  {
-            for j in 1..y_size - 1 {
-              let calculated = data.compute_visibility((i, j));
-              if calculated > max {
-                max = calculated;
-                current_coord = (i,j);
-              }
-            }
-        }
-
-        //println!("{:?}", data);
-        println!("{:?}", max);
-        println!("{:?}", current_coord);
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day08>(50, 1..=8, |text, _, &visible, &best| {
+            let trees: Vec<Vec<u8>> = text.lines().map(|line| line.bytes().collect()).collect();
+            let (width, height) = (trees[0].len() as isize, trees.len() as isize);
+
+            // how far a tree sees in each direction, and whether it sees past the edge
+            let views = |x: isize, y: isize| {
+                [(0, -1), (0, 1), (-1, 0), (1, 0)].map(|(dx, dy)| {
+                    let (mut px, mut py, mut seen) = (x + dx, y + dy, 0);
+                    while (0..width).contains(&px) && (0..height).contains(&py) {
+                        seen += 1;
+                        if trees[py as usize][px as usize] >= trees[y as usize][x as usize] {
+                            return (seen, false);
+                        }
+                        (px, py) = (px + dx, py + dy);
+                    }
+                    (seen, true)
+                })
+            };
+            let all = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+
+            assert_eq!(all.clone().filter(|&(x, y)| views(x, y).iter().any(|&(_, out)| out)).count(), visible);
+            assert_eq!(all.map(|(x, y)| views(x, y).iter().map(|&(seen, _)| seen).product::<usize>()).max().unwrap(), best);
+            Ok(())
+        })
     }
 }
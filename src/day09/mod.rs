@@ -1,146 +1,256 @@
-use std::{
-    collections::HashSet,
-    fmt::{Debug, Write},
-    ops::{Add, Sub, AddAssign, SubAssign, BitOrAssign},
+//! Day 9: Rope Bridge.
+
+use std::collections::HashSet;
+
+use eyre::Result;
+
+use crate::{
+    generate::{Generate, Rng},
+    grid,
+    parse::{self, OrExpected},
+    point::Vec2,
+    simulation::{Animated, Simulation},
+    solution::{Part, Solution},
 };
-use itertools::Itertools;
 
 
-#[derive(Debug)]
-enum Direction {
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT,
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
 impl Direction {
-  fn delta(&self) -> (i8, i8) {
+  fn delta(&self) -> Vec2 {
     match self {
-        Direction::UP => (0, -1),
-        Direction::DOWN => (0, 1),
-        Direction::LEFT => (-1, 0),
-        Direction::RIGHT => (1, 0),
+        Direction::Up => Vec2::UP,
+        Direction::Down => Vec2::DOWN,
+        Direction::Left => Vec2::LEFT,
+        Direction::Right => Vec2::RIGHT,
     }
   }
 }
 
-const KNOTS: usize = 10;
-
 #[derive(Debug)]
-struct Grid {
-    current_pos: [(isize, isize); KNOTS],
-    tail_histo: HashSet<(isize, isize)>,
+struct Grid<const KNOTS: usize> {
+    current_pos: [Vec2; KNOTS],
+    tail_histo: HashSet<Vec2>,
     moving_index: usize
 }
 
-impl Grid {
+impl<const KNOTS: usize> Grid<KNOTS> {
     pub fn new() -> Self {
         let mut tail_histo = HashSet::new();
-        tail_histo.insert((0, 0));
+        tail_histo.insert(Vec2::ZERO);
 
-        let mut g = Grid {
+        Grid {
             tail_histo,
-            current_pos: Default::default(),
+            current_pos: [Vec2::ZERO; KNOTS],
             moving_index: 0
-        };
-
-        g
+        }
     }
 
     fn moving(&mut self, direction: &Direction) {
-  
-      
       let delta = direction.delta();
-      println!("Moving {direction:?} ({delta:?})");
 
-      let old_head = self.current_pos[self.moving_index];
-      let new_head = (old_head.0 + delta.0 as isize, old_head.1 + delta.1 as isize);
-
-      self.current_pos[0] = new_head;
+      self.current_pos[0] = self.current_pos[self.moving_index] + delta;
 
       for i in 1..KNOTS {
-        match shoud_follow(self.current_pos[i-1], self.current_pos[i]) {
-            Some((dx, dy)) => {
-              self.current_pos[i] = (self.current_pos[i].0 + dx, self.current_pos[i].1 + dy);
-            },
-            None => (),
+        if let Some(step) = shoud_follow(self.current_pos[i-1], self.current_pos[i]) {
+          self.current_pos[i] += step;
         }
       }
       
-      self.tail_histo.insert(self.current_pos.last().unwrap().clone());
-
-      println!("curr stact : {:?}", self.current_pos);
-      //println!("tail_histo : {:?}", self.tail_histo);
-      
+      self.tail_histo.insert(*self.current_pos.last().unwrap());
     }
 
 }
 
+// the rope following the moves one step at a time
+struct Motions<const KNOTS: usize> {
+    rope: Grid<KNOTS>,
+    steps: Vec<Direction>,
+    next: usize,
+}
 
+impl<const KNOTS: usize> Motions<KNOTS> {
+    fn new(moves: &[(Direction, u8)]) -> Self {
+        let steps = moves
+            .iter()
+            .flat_map(|&(dir, dist)| std::iter::repeat_n(dir, dist as usize))
+            .collect();
 
-fn shoud_follow(head: (isize, isize), tail: (isize, isize)) -> Option<(isize, isize)> {
+        Motions { rope: Grid::new(), steps, next: 0 }
+    }
+}
 
-  let dist = (head.0 - tail.0, head.1 - tail.1);
+impl<const KNOTS: usize> Simulation for Motions<KNOTS> {
+    fn step(&mut self) {
+        if let Some(dir) = self.steps.get(self.next) {
+            self.rope.moving(dir);
+            self.next += 1;
+        }
+    }
 
-  if dist.0.abs() <= 1 && dist.1.abs() <= 1 {
+    fn is_done(&self) -> bool {
+        self.next == self.steps.len()
+    }
+
+    // the knots over the cells the tail visited, from the head `H` down to the tail
+    fn render(&self) -> String {
+        let seen = || self.rope.tail_histo.iter().chain(&self.rope.current_pos);
+        let min = Vec2::new(seen().map(|p| p.x).min().unwrap(), seen().map(|p| p.y).min().unwrap());
+        let max = Vec2::new(seen().map(|p| p.x).max().unwrap(), seen().map(|p| p.y).max().unwrap());
+
+        let mut cells = grid::Grid::new((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize, '.')
+            .with_origin(min);
+        for &pos in &self.rope.tail_histo {
+            cells[pos] = '#';
+        }
+        cells[Vec2::ZERO] = 's';
+        for (i, &pos) in self.rope.current_pos.iter().enumerate().rev() {
+            cells[pos] = if i == 0 { 'H' } else { char::from_digit(i as u32, 36).unwrap() };
+        }
+
+        cells.to_string()
+    }
+
+    fn status(&self) -> String {
+        format!("{} of {} steps, tail visited {}", self.next, self.steps.len(), self.rope.tail_histo.len())
+    }
+}
+
+
+
+fn shoud_follow(head: Vec2, tail: Vec2) -> Option<Vec2> {
+  if head.chebyshev(tail) <= 1 {
     return None;
   }
 
-  return Some((move_to_do(dist.0), move_to_do(dist.1)));
+  Some((head - tail).signum())
+}
+
 
+fn tail_positions<const KNOTS: usize>(moves: &[(Direction, u8)]) -> usize {
+    let mut motions = Motions::<KNOTS>::new(moves);
+    motions.run();
+
+    motions.rope.tail_histo.len()
 }
 
-fn move_to_do(i : isize)-> isize {
-  if i == 0 {
-    return 0;
-  }
-  return i / i.abs();
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<(Direction, u8)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse::each_line(input, |line| {
+              let (d, n) = line.split_once(' ').or_expected("`<U|D|L|R> <steps>`")?;
+              let dist = parse::number(n, "a number of steps")?;
+              let d = match d {
+                "U" => Direction::Up,
+                "D" => Direction::Down,
+                "L" => Direction::Left,
+                "R" => Direction::Right,
+                _ => return Err(parse::expected(format!("a direction U, D, L or R, found {d:?}"))),
+              };
+
+              Ok((d, dist))
+            })
+    }
+
+    fn part1(moves: &Self::Input) -> usize {
+        tail_positions::<2>(moves)
+    }
+
+    fn part2(moves: &Self::Input) -> usize {
+        tail_positions::<10>(moves)
+    }
+}
+
+impl Animated for Day09 {
+    fn simulation(moves: Self::Input, part: Part) -> Box<dyn Simulation> {
+        match part {
+            Part::One => Box::new(Motions::<2>::new(&moves)),
+            Part::Two => Box::new(Motions::<10>::new(&moves)),
+        }
+    }
 }
 
 
+impl Generate for Day09 {
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..20 * size).map(|_| format!("{} {}\n", rng.word("UDLR", 1), 1 + rng.below(2 * size + 1))).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::input;
+
     use super::*;
 
     #[test]
     fn test() {
-      assert_eq!(None, shoud_follow((0,0), (0,0)));
-      assert_eq!(None, shoud_follow((1,0), (0,0)));
-      assert_eq!(Some((1,0)), shoud_follow((2,0), (0,0)));
-      assert_eq!(Some((1,1)), shoud_follow((2,1), (0,0)));
-      assert_eq!(Some((1,-1)), shoud_follow((5, -2), (4,0)));
+      assert_eq!(None, shoud_follow(Vec2::new(0,0), Vec2::new(0,0)));
+      assert_eq!(None, shoud_follow(Vec2::new(1,0), Vec2::new(0,0)));
+      assert_eq!(Some(Vec2::new(1,0)), shoud_follow(Vec2::new(2,0), Vec2::new(0,0)));
+      assert_eq!(Some(Vec2::new(1,1)), shoud_follow(Vec2::new(2,1), Vec2::new(0,0)));
+      assert_eq!(Some(Vec2::new(1,-1)), shoud_follow(Vec2::new(5, -2), Vec2::new(4,0)));
     }
 
     #[test]
-    fn adventofcode1() -> Result<(), String> {
-        let mut grid = Grid::new();
-        println!("{grid:?}");
-
-        include_str!("input.txt")
-            .lines()
-            .map(|f| f.split(" "))
-            .map(|mut d| (d.next().unwrap(), d.next().unwrap()))
-            .map(|(d, n)| {
-              let dist = n.parse::<u8>().unwrap();
-              let d = match d {
-                "U" => Direction::UP,
-                "D" => Direction::DOWN,
-                "L" => Direction::LEFT,
-                "R" => Direction::RIGHT,
-                _ => unreachable!()
-              };
+    fn adventofcode1() -> Result<()> {
+        let moves = Day09::parse(&input::real(9)?)?;
+        assert_eq!(6354, Day09::part1(&moves));
 
-              (d, dist)
-            }).for_each(|(dir, dist)| {
-              for _ in 0..dist {
-                grid.moving(&dir);
-              }
-            });
+        Ok(())
+    }
 
-            //println!("{grid:#?}");
-            println!("Result : {}", grid.tail_histo.len());
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let moves = Day09::parse(&input::real(9)?)?;
+        assert_eq!(2651, Day09::part2(&moves));
 
         Ok(())
     }
+
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        // every knot steps straight or diagonally towards the one ahead once it is two away
+        fn tail_visits(text: &str, knots: usize) -> usize {
+            let mut rope = vec![(0i32, 0i32); knots];
+            let mut visited = HashSet::from([(0, 0)]);
+            for line in text.lines() {
+                let (direction, steps) = line.split_once(' ').unwrap();
+                let (dx, dy) = match direction {
+                    "U" => (0, -1),
+                    "D" => (0, 1),
+                    "L" => (-1, 0),
+                    _ => (1, 0),
+                };
+                for _ in 0..steps.parse().unwrap() {
+                    rope[0] = (rope[0].0 + dx, rope[0].1 + dy);
+                    for i in 1..knots {
+                        let (x, y) = (rope[i - 1].0 - rope[i].0, rope[i - 1].1 - rope[i].1);
+                        if x.abs() > 1 || y.abs() > 1 {
+                            rope[i] = (rope[i].0 + x.signum(), rope[i].1 + y.signum());
+                        }
+                    }
+                    visited.insert(rope[knots - 1]);
+                }
+            }
+
+            visited.len()
+        }
+
+        crate::generate::check::<Day09>(30, 1..=8, |text, _, &short, &long| {
+            assert_eq!((tail_visits(text, 2), tail_visits(text, 10)), (short, long));
+            Ok(())
+        })
+    }
 }
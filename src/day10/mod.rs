@@ -1,121 +1,243 @@
+//! Day 10: Cathode-Ray Tube.
+
 use std::str::FromStr;
 
-use nom::{
-  branch::alt,
-  bytes::complete::tag,
-  character::complete::{alpha1, char, digit1, multispace0, multispace1, one_of},
-  combinator::{cut, map, map_res, opt},
-  error::{context, VerboseError},
-  multi::many0,
-  sequence::{delimited, preceded, terminated, tuple},
-  IResult, Parser,
+use eyre::{Report, Result};
+
+use crate::{
+    generate::{Generate, Rng},
+    log::debug,
+    parse,
+    simulation::{Animated, Simulation},
+    solution::{Part, Solution},
 };
 
 
 
-#[derive(Debug)]
-enum Instruction {
+#[derive(Debug, Clone)]
+pub enum Instruction {
   Noop,
   AddX(isize)
 }
 
 impl FromStr for Instruction {
-    type Err = String;
+    type Err = Report;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
         if s == "noop" {
           return Ok(Instruction::Noop);
         }
       
-        let mut splits = s.split(" ");
-        let addx = splits.next().unwrap();
-        if addx == "addx" {
-          let num = splits.next().unwrap();
-          let num = num.parse::<isize>().map_err(|_| "Not a number".to_owned())?;
+        if let Some(num) = s.strip_prefix("addx ") {
+          let num = parse::number(num, "a number to add")?;
 
           return Ok(Instruction::AddX(num));
         }
 
-        return Err("Not a valid ".to_owned());
+        Err(parse::expected("`noop` or `addx <number>`"))
+    }
+}
+
+fn register_values(instructions: &[Instruction]) -> Vec<isize> {
+  let mut vec: Vec<isize> = vec![1];
+
+  let instuctions = instructions
+    .iter()
+    .cloned()
+    .flat_map(|i| {
+      match i {
+          Instruction::Noop => vec![Instruction::Noop],
+          Instruction::AddX(x) => vec![Instruction::Noop, Instruction::AddX(x)],
+      }
+    });
+
+    for instruction in instuctions {
+      let prev = *vec.last().unwrap();
+      match instruction {
+          Instruction::Noop => vec.push(prev),
+          Instruction::AddX(x) => vec.push(prev + x),
+      }
+    }
+
+    vec
+}
+
+const CRT_WIDTH: usize = 40;
+const CRT_HEIGHT: usize = 6;
+
+// the last cycle part 1 samples the signal strength at
+const LAST_SAMPLE: usize = 220;
+
+// the CRT drawing one pixel per cycle, lit when the sprite covers it and is not off the left edge
+struct Crt {
+  values: Vec<isize>,
+  cycle: usize,
+}
+
+impl Simulation for Crt {
+  fn step(&mut self) {
+    if !self.is_done() {
+      self.cycle += 1;
+    }
+  }
+
+  fn is_done(&self) -> bool {
+    self.cycle == self.values.len().min(CRT_WIDTH * CRT_HEIGHT)
+  }
+
+  // pixels not drawn yet are left blank
+  fn render(&self) -> String {
+    let mut out = String::new();
+    for y in 0..CRT_HEIGHT {
+      for x in 0..CRT_WIDTH {
+        let i = x + y * CRT_WIDTH;
+        let lit = self.values.get(i).is_some_and(|&v| v >= 0 && v.abs_diff(x as isize) <= 1);
+        out.push(match (i < self.cycle, lit) {
+          (false, _) => ' ',
+          (true, true) => '#',
+          (true, false) => '.',
+        });
+      }
+      out.push('\n');
+    }
+
+    out
+  }
+
+  fn status(&self) -> String {
+    match self.values.get(self.cycle) {
+      Some(x) => format!("cycle {}, X = {x}", self.cycle + 1),
+      None => format!("cycle {}", self.cycle),
     }
+  }
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+  type Input = Vec<Instruction>;
+  type Answer1 = Result<isize>;
+  type Answer2 = String;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    parse::each_line(input, Instruction::from_str)
+  }
+
+  fn part1(instructions: &Self::Input) -> Result<isize> {
+    let vec = register_values(instructions);
+    let cycles = vec.len() - 1;
+    if cycles < LAST_SAMPLE {
+      return Err(parse::expected(format!("a program running at least {LAST_SAMPLE} cycles, found {cycles}")));
+    }
+
+    //println!("{vec:?}");
+    let mut acc = 0isize;
+    [20isize, 60, 100, 140, 180, 220].iter().for_each(|i| {
+      let index = (*i - 1) as usize;
+      debug!("At {} : {}", i, vec[index]);
+      acc += i * vec[index];
+    });
+
+    Ok(acc)
+  }
+
+  fn part2(instructions: &Self::Input) -> String {
+    let mut crt = Crt { values: register_values(instructions), cycle: 0 };
+    crt.run();
+
+    crt.render().trim_end().to_string()
+  }
+}
+
+impl Animated for Day10 {
+  fn simulation(instructions: Self::Input, _: Part) -> Box<dyn Simulation> {
+    Box::new(Crt { values: register_values(&instructions), cycle: 0 })
+  }
+}
+
+impl Generate for Day10 {
+  // enough cycles for the whole screen, `size` only changes how far X wanders
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let (mut out, mut cycles) = (String::new(), 0);
+    while cycles < CRT_WIDTH * CRT_HEIGHT {
+      if rng.one_in(3) {
+        out += "noop\n";
+        cycles += 1;
+      } else {
+        out += &format!("addx {}\n", rng.range(-(size as isize) - 2..=size as isize + 2));
+        cycles += 2;
+      }
+    }
+
+    out
+  }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::{HashMap, HashSet}, ffi::IntoStringError};
+    use crate::input;
 
     use super::*;
 
 
   #[test]
-  fn adventofcode1() {
+  fn adventofcode1() -> Result<()> {
+    let instructions = Day10::parse(&input::real(10)?)?;
+    assert_eq!(14240, Day10::part1(&instructions)?);
 
-    let mut vec: Vec<isize> = vec![1];
+    Ok(())
+  }
 
-    let instuctions = include_str!("input.txt")
-      .lines()
-      .filter_map(|s| Instruction::from_str(s).ok())
-      .flat_map(|i| {
-        match i {
-            Instruction::Noop => vec![Instruction::Noop],
-            Instruction::AddX(x) => vec![Instruction::Noop, Instruction::AddX(x)],
-        }
-      });
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let instructions = Day10::parse(&input::real(10)?)?;
+    assert_eq!(
+      "###..#....#..#.#....#..#.###..####.#..#.\n\
+       ...#.#....#..#.#....#.#..#..#....#.#..#.\n\
+       #..#.#....#..#.#....##...###....#..####.\n\
+       ###..#....#..#.#....#.#..#..#..#...#..#.\n\
+       .....#....#..#.#....#.#..#..#.#....#..#.\n\
+       .....####..##..####.#..#.###..####.#..#.",
+      Day10::part2(&instructions)
+    );
+
+    Ok(())
+  }
 
-      for instruction in instuctions {
-        let prev = vec.last().unwrap().clone();
-        match instruction {
-            Instruction::Noop => vec.push(prev),
-            Instruction::AddX(x) => vec.push(prev + x),
-        }
-      }
+  #[test]
+  fn short_program() -> Result<()> {
+    let instructions = Day10::parse("noop\naddx 3\n")?;
+    let err = Day10::part1(&instructions).unwrap_err();
+    assert_eq!("expected a program running at least 220 cycles, found 3", err.to_string());
+    assert!(Day10::part2(&instructions).starts_with("###"));
+
+    Ok(())
+  }
 
-      //println!("{vec:?}");
-      let mut acc = 0isize;
-      [20isize, 60, 100, 140, 180, 220].iter().for_each(|i| {
-        let index = (*i - 1) as usize;
-        println!("At {} : {}", i, vec[index]);
-        acc = acc + i * vec[index];
-      });
-      println!("Result : {}", acc);
-      let mut crt = [["."; 40]; 6];
-
-      for (i, v) in vec.iter().enumerate() {
-        let y = i / 40;
-        let x = i - y * 40;
-
-        println!("Pos Check ({x},{y}) ({i}) curr : ({v})");
-
-        let range = {
-          if x == 0 {
-            0..=1
-          }else {
-            x-1..=x+1
-          }
-        };
-        
-        let v = v.clone();
-        //print!("Checking {} -> {:?}", v, range);
-        if v.abs() == v {
-          let v = v as usize;
-          if range.contains(&v) {
-            //println!(" PRINT");
-            crt[y][x] = "#";
-          }else {
-            //println!(" NO PRINT")
-          }
-        }else {
-          //println!(" NO PRINT (negetive) ");
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day10>(30, 1..=20, |text, _, strength, screen| {
+      // X during each cycle, addx taking two
+      let mut x = 1;
+      let mut during = vec![];
+      for line in text.lines() {
+        during.push(x);
+        if let Some(n) = line.strip_prefix("addx ") {
+          during.push(x);
+          x += n.parse::<isize>().unwrap();
         }
-        
       }
 
+      let expected: isize = [20, 60, 100, 140, 180, 220].iter().map(|&cycle| cycle * during[cycle as usize - 1]).sum();
+      assert_eq!(expected, *strength.as_ref().unwrap());
 
-      println!("CRT");
-      for i in crt {
-        println!("{}", i.join(""));
+      let pixels: Vec<char> = screen.chars().filter(|&c| c != '\n').collect();
+      assert_eq!(CRT_WIDTH * CRT_HEIGHT, pixels.len());
+      for (i, &pixel) in pixels.iter().enumerate() {
+        let x = during[i];
+        assert_eq!(x >= 0 && x.abs_diff((i % CRT_WIDTH) as isize) <= 1, pixel == '#', "pixel {i}");
       }
-
-      
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
@@ -1,4 +1,18 @@
-use std::{fmt::Debug, str::{Lines, FromStr}};
+//! Day 11: Monkey in the Middle.
+
+use std::fmt::Debug;
+
+use eyre::Result;
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  character::complete::{line_ending, space0},
+  combinator::{map, value, verify},
+  sequence::{pair, preceded},
+  Parser,
+};
+
+use crate::{cycle, generate::{Generate, Rng}, log::trace, parse::{self, Expected, Parsed}, solution::Solution};
 
 
 #[derive(Clone)]
@@ -8,30 +22,28 @@ enum Operation {
   Square
 }
 
-impl FromStr for Operation {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-      let mut s = s.split(" ");
-
-      match (s.next().unwrap(), s.next().unwrap(), s.next().unwrap()) {
-        ("old", "*", "old") => Ok(Operation::Square),
-        ("old", "*", v) => Ok(Operation::Mult(v.parse::<usize>().unwrap())),
-        ("old", "+", v) => Ok(Operation::Add(v.parse::<usize>().unwrap())),
-        _ => unreachable!()
-      }
+fn operation(input: &str) -> Parsed<'_, Operation> {
+    let operations = alt((
+      value(Operation::Square, tag("old * old")),
+      map(preceded(tag("old * "), parse::integer), Operation::Mult),
+      map(preceded(tag("old + "), parse::integer), Operation::Add),
+    ));
 
+    parse::named("`old * <n>`, `old + <n>` or `old * old`", operations)(input)
+}
 
-    }
+// part 2 keeps worry levels down modulo the product of these
+fn divisor(input: &str) -> Parsed<'_, usize> {
+    parse::named("a divisor above 0", verify(parse::integer, |&divisor: &usize| divisor > 0))(input)
 }
 
-struct Monkey {
+#[derive(Clone)]
+pub struct Monkey {
     items: Vec<usize>,
     operand: Operation,
     test: usize,
     target_true: usize,
     target_false: usize,
-    nbr_inspected: usize
 }
 
 impl Debug for Monkey {
@@ -40,40 +52,41 @@ impl Debug for Monkey {
             .field("items", &self.items)
             .field("target_true", &self.target_true)
             .field("target_false", &self.target_false)
-            .field("nbr_inspected", &self.nbr_inspected)
             .finish()
     }
 }
 
+// the next line of a monkey, indented and with a single hole
+fn field<'a, T>(template: &'static str, hole: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, T> {
+    let prefix = template.split("{}").next().unwrap_or_default();
+    let next_line = parse::named(format!("`{prefix}...` on the next line"), pair(line_ending, space0));
+
+    map(preceded(next_line, parse::template(template, hole)), |[value]| value)
+}
+
 impl Monkey {
-    fn new(lines: &mut Lines) -> Self {
-        let items: Vec<_> = lines.next().unwrap().trim()["Starting items: ".len()..].split(",").map(|f| f.trim()).filter_map(|e| e.parse::<usize>().ok()).collect();
-        let operand = Operation::from_str(&lines.next().unwrap().trim()["Operation: new = ".len()..]).unwrap();
-        let test = lines.next().unwrap().trim()["Test: divisible by ".len()..]
-            .parse::<usize>()
-            .unwrap();
-        let target_true = lines.next().unwrap().trim()["If true: throw to monkey ".len()..]
-            .parse::<usize>()
-            .unwrap();
-        let target_false = lines.next().unwrap().trim()["If false: throw to monkey ".len()..]
-            .parse::<usize>()
-            .unwrap();
-
-        lines.next();
-
-
-        Monkey {
-          target_true,
-          target_false,
-          test,
-          items,
-          operand,
-          nbr_inspected: 0
+    fn parser<'a>(index: usize) -> impl FnMut(&'a str) -> Parsed<'a, Self> {
+        let header = format!("Monkey {index}:");
+        move |input| {
+            let (input, _) = parse::named(format!("`{header}`"), tag(header.as_str()))(input)?;
+            let (input, items) = field("Starting items: {}", parse::comma_list(parse::integer))(input)?;
+            let (input, operand) = field("Operation: new = {}", operation)(input)?;
+            let (input, test) = field("Test: divisible by {}", divisor)(input)?;
+            let (input, target_true) = field("If true: throw to monkey {}", parse::integer)(input)?;
+            let (input, target_false) = field("If false: throw to monkey {}", parse::integer)(input)?;
+
+            Ok((input, Monkey {
+              target_true,
+              target_false,
+              test,
+              items,
+              operand,
+            }))
         }
     }
 
     fn test(&self, to_check: usize) -> bool {
-      to_check % self.test == 0
+      to_check.is_multiple_of(self.test)
     }
 
     fn operate(&self, input: usize) -> usize {
@@ -83,85 +96,228 @@ impl Monkey {
         Operation::Square => input * input,
       }
     }
+
+    fn checked_operate(&self, input: usize) -> Option<usize> {
+      match self.operand {
+        Operation::Mult(x) => x.checked_mul(input),
+        Operation::Add(x) => x.checked_add(input),
+        Operation::Square => input.checked_mul(input),
+      }
+    }
+
+    fn random(rng: &mut Rng, index: usize, monkeys: usize, test: usize) -> Self {
+      let operand = match rng.below(8) {
+        0 => Operation::Square,
+        1..=3 => Operation::Add(1 + rng.below(8)),
+        _ => Operation::Mult(2 + rng.below(18)),
+      };
+      let target_true = (index + 1 + rng.below(monkeys - 1)) % monkeys;
+      let target_false = loop {
+        let target = rng.below(monkeys);
+        if target != index && (target != target_true || monkeys == 2) {
+          break target;
+        }
+      };
+
+      Monkey {
+        items: (0..1 + rng.below(4)).map(|_| 50 + rng.below(50)).collect(),
+        operand,
+        test,
+        target_true,
+        target_false,
+      }
+    }
+
+    fn render(&self, index: usize) -> String {
+      let items = self.items.iter().map(usize::to_string).collect::<Vec<_>>().join(", ");
+      let operation = match self.operand {
+        Operation::Mult(x) => format!("old * {x}"),
+        Operation::Add(x) => format!("old + {x}"),
+        Operation::Square => "old * old".to_owned(),
+      };
+
+      format!(
+        "Monkey {index}:\n  Starting items: {items}\n  Operation: new = {operation}\n  Test: divisible by {}\n    \
+         If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+        self.test, self.target_true, self.target_false,
+      )
+    }
+}
+
+// an item between two rounds, with how often each monkey inspected it so far
+struct Item {
+    monkey: usize,
+    worry: usize,
+    inspected: Vec<u128>,
+}
+
+fn monkey_business(monkeys: &[Monkey], rounds: usize, relief: impl Fn(usize) -> usize) -> usize {
+    // items never meet, so each one can be followed alone until it is back where it was
+    let mut inspected = vec![0; monkeys.len()];
+    for (holder, monkey) in monkeys.iter().enumerate() {
+      for &worry in monkey.items.iter() {
+        let item = Item { monkey: holder, worry, inspected: vec![0; monkeys.len()] };
+        let history = cycle::detect(
+            item,
+            rounds,
+            |item| loop {
+              let m = &monkeys[item.monkey];
+              item.inspected[item.monkey] += 1;
+              item.worry = relief(m.operate(item.worry));
+
+              let target = if m.test(item.worry) { m.target_true } else { m.target_false };
+              // monkeys further down the list still get their turn this round
+              let next_round = target < item.monkey;
+              item.monkey = target;
+              if next_round {
+                break;
+              }
+            },
+            |item| (item.monkey, item.worry),
+            |item| item.inspected.clone(),
+        );
+        trace!("item {worry} of monkey {holder}: {:?}", history.cycle());
+
+        let counts = history.extrapolate(rounds as u128).expect("the item either loops or is followed to the end");
+        for (total, count) in inspected.iter_mut().zip(counts) {
+          *total += count;
+        }
+      }
+    }
+
+    inspected.sort_unstable_by(|a, b| b.cmp(a));
+    inspected.iter().take(2).product::<u128>() as usize
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        // monkeys come in order, each header naming the next one
+        let mut index = 0;
+        let monkeys = parse::whole(input, parse::blocks(|input| {
+          let (rest, monkey) = Monkey::parser(index)(input)?;
+          index += 1;
+          Ok((rest, monkey))
+        }))?;
+
+        for (index, monkey) in monkeys.iter().enumerate() {
+          for target in [monkey.target_true, monkey.target_false] {
+            if target >= monkeys.len() || target == index {
+              return Err(parse::expected(format!("monkey {index} to throw to another of the {} monkeys, found {target}", monkeys.len())));
+            }
+          }
+        }
+
+        Ok(monkeys)
+    }
+
+    fn part1(monkeys: &Self::Input) -> usize {
+        monkey_business(monkeys, 20, |worry| worry / 3)
+    }
+
+    fn part2(monkeys: &Self::Input) -> usize {
+        let modulo: usize = monkeys.iter().map(|m| m.test).product();
+
+        monkey_business(monkeys, 10000, |worry| worry % modulo)
+    }
+}
+
+// whether every worry level stays in range for the 20 rounds of part 1, with nothing to keep it down but relief
+fn fits_part1(monkeys: &[Monkey]) -> bool {
+    let mut held: Vec<Vec<usize>> = monkeys.iter().map(|m| m.items.clone()).collect();
+    for _ in 0..20 {
+      for (index, monkey) in monkeys.iter().enumerate() {
+        for worry in std::mem::take(&mut held[index]) {
+          let Some(worry) = monkey.checked_operate(worry) else { return false };
+          let worry = worry / 3;
+          held[if monkey.test(worry) { monkey.target_true } else { monkey.target_false }].push(worry);
+        }
+      }
+    }
+
+    true
+}
+
+impl Generate for Day11 {
+    // monkeys test distinct primes, as in the puzzle, so part 2 can work modulo their product
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let count = 2 + rng.below(size.min(7));
+        let monkeys = loop {
+          let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+          rng.shuffle(&mut primes);
+          let monkeys: Vec<Monkey> = (0..count).map(|index| Monkey::random(rng, index, count, primes[index])).collect();
+          if fits_part1(&monkeys) {
+            break monkeys;
+          }
+        };
+
+        monkeys.iter().enumerate().map(|(index, monkey)| monkey.render(index)).collect::<Vec<_>>().join("\n")
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::input;
+
     use super::*;
 
-    // #[test]
-    // fn adventofcode1() {
-    //     let mut lines = include_str!("input.txt").lines();
-    //     let mut monkeys: Vec<Monkey> = vec![];
-    //     while let Some(_) = lines.next() {
-    //       monkeys.push(Monkey::new(&mut lines));
-    //     }
-
-    //     for i in 0..20 {
-    //       for m in 0..monkeys.len() {
-    //         let mut to_move: Vec<(usize, usize)> = vec![];
-    //         for worry in monkeys[m].items.iter() {
-    //           let new_worry = (monkeys[m].operation)(*worry);
-    //           let new_worry = new_worry / 3;
-    //           let test_result = (monkeys[m].test)(new_worry);
-    //           if test_result {
-    //             let target = monkeys[m].target_true;
-    //             to_move.push((target, new_worry));
-    //           }else {
-    //             let target = monkeys[m].target_false;
-    //             to_move.push((target, new_worry));
-    //           }
-    //         }
-
-    //         monkeys[m].nbr_inspected += monkeys[m].items.len();
-    //         monkeys[m].items = vec![];
-    //         for (target, val) in to_move {
-    //           monkeys[target].items.push(val);
-    //         }
-    //       }
-    //     }
-
-    //     dbg!(monkeys);
-    // }
+    #[test]
+    fn adventofcode1() -> Result<()> {
+        let monkeys = Day11::parse(&input::real(11)?)?;
+        assert_eq!(76728, Day11::part1(&monkeys));
+
+        Ok(())
+    }
 
     #[test]
-    fn adventofcode2() {
-        let mut lines = include_str!("input.txt").lines();
-        let mut monkeys: Vec<Monkey> = vec![];
-        while let Some(_) = lines.next() {
-          monkeys.push(Monkey::new(&mut lines));
-        }
+    fn adventofcode2() -> Result<()> {
+        let monkeys = Day11::parse(&input::real(11)?)?;
+        assert_eq!(21553910156, Day11::part2(&monkeys));
 
-        let modulo: usize = monkeys.iter().map(|m| m.test).product();
+        Ok(())
+    }
 
-        for i in 0..10000 {
-          for m in 0..monkeys.len() {
-            let mut to_move: Vec<(usize, usize)> = vec![];
-            for worry in monkeys[m].items.iter() {
-              
-              let new_worry = monkeys[m].operate(*worry);
-              let new_worry = new_worry % modulo;
-              //println!("{i} For monkey ({m}) : base {worry:?} -> {new_worry}");
-
-              let test_result = monkeys[m].test(new_worry);
-              if test_result {
-                let target = monkeys[m].target_true;
-                to_move.push((target, new_worry));
-              }else {
-                let target = monkeys[m].target_false;
-                to_move.push((target, new_worry));
-              }
-            }
+    #[test]
+    fn zero_divisor() {
+        let monkeys = "Monkey 0:\n  Starting items: 79\n  Operation: new = old * 19\n  Test: divisible by 0\n    \
+                       If true: throw to monkey 1\n    If false: throw to monkey 1\n";
+        let err = Day11::parse(monkeys).unwrap_err();
+
+        assert_eq!("line 4: \"  Test: divisible by 0\": expected a divisor above 0 at column 22", format!("{err:#}"));
+    }
 
-            monkeys[m].nbr_inspected += monkeys[m].items.len();
-            monkeys[m].items = vec![];
-            for (target, val) in to_move {
-              monkeys[target].items.push(val);
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        // every round played in full, the way the puzzle tells it
+        fn play(monkeys: &[Monkey], rounds: usize, relief: impl Fn(usize) -> usize) -> usize {
+            let mut held: Vec<Vec<usize>> = monkeys.iter().map(|m| m.items.clone()).collect();
+            let mut inspected = vec![0; monkeys.len()];
+            for _ in 0..rounds {
+                for (index, monkey) in monkeys.iter().enumerate() {
+                    for worry in std::mem::take(&mut held[index]) {
+                        inspected[index] += 1;
+                        let worry = relief(monkey.operate(worry));
+                        held[if monkey.test(worry) { monkey.target_true } else { monkey.target_false }].push(worry);
+                    }
+                }
             }
-          }
+            inspected.sort_unstable_by(|a, b| b.cmp(a));
+
+            inspected[0] * inspected[1]
         }
 
-        dbg!(monkeys);
+        crate::generate::check::<Day11>(20, 1..=7, |_, monkeys, &part1, &part2| {
+            let modulo: usize = monkeys.iter().map(|m| m.test).product();
+
+            assert_eq!(play(monkeys, 20, |worry| worry / 3), part1);
+            assert_eq!(play(monkeys, 10000, |worry| worry % modulo), part2);
+            Ok(())
+        })
     }
 
 }
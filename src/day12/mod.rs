@@ -1,30 +1,42 @@
-use std::{
-    collections::{HashMap, HashSet},
-    time::Duration,
-};
+//! Day 12: Hill Climbing Algorithm.
+
+use eyre::{Report, Result};
 
-use itertools::Itertools;
+use crate::{
+    generate::{Generate, Rng},
+    grid::{self, CellChar},
+    image::{Palette, Rgb},
+    parse,
+    point::Vec2,
+    search::{self, Path, Paths},
+    simulation::{Animated, Simulation},
+    solution::{Part, Solution},
+};
 
-#[derive(Debug, PartialEq, Eq)]
+/// One cell of the heightmap.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TerrainType {
     Start,
     Target,
     Terrain(usize),
 }
 
-impl From<char> for TerrainType {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for TerrainType {
+    type Error = Report;
+
+    fn try_from(c: char) -> Result<Self> {
         match c {
-            'S' => TerrainType::Start,
-            'E' => TerrainType::Target,
-            'a'..='z' => TerrainType::Terrain(c as usize - b'a' as usize),
-            _ => unreachable!(),
+            'S' => Ok(TerrainType::Start),
+            'E' => Ok(TerrainType::Target),
+            'a'..='z' => Ok(TerrainType::Terrain(c as usize - b'a' as usize)),
+            _ => Err(parse::expected(format!("`S`, `E` or an elevation a-z, found {c:?}"))),
         }
     }
 }
 
 impl TerrainType {
-    fn heigh(&self) -> usize {
+    /// Elevation from 0 (`a` and the start) to 25 (`z` and the target).
+    pub fn heigh(&self) -> usize {
         match self {
             TerrainType::Start => 0,
             TerrainType::Target => (b'z' - b'a') as usize,
@@ -33,165 +45,278 @@ impl TerrainType {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-struct GridCoord {
-    x: usize,
-    y: usize,
+impl CellChar for TerrainType {
+    fn char(&self) -> char {
+        match self {
+            TerrainType::Start => 'S',
+            TerrainType::Target => 'E',
+            TerrainType::Terrain(v) => (b'a' + *v as u8) as char,
+        }
+    }
+}
+
+/// The heightmap, searched breadth first from one or more starting cells.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    map: grid::Grid<TerrainType>,
 }
 
-impl From<(usize, usize)> for GridCoord {
-    fn from((x, y): (usize, usize)) -> Self {
-        GridCoord { x, y }
+impl Grid {
+    /// Parses rows of `a`-`z` with exactly one `S` start and one `E` target.
+    pub fn new(input: &str) -> Result<Self> {
+        let map = grid::Grid::parse(input, TerrainType::try_from)?;
+
+        for (cell, name) in [(TerrainType::Start, "start `S`"), (TerrainType::Target, "target `E`")] {
+            let count = map.values().filter(|&c| *c == cell).count();
+            if count != 1 {
+                return Err(parse::expected(format!("exactly one {name}, found {count}")));
+            }
+        }
+
+        Ok(Self { map })
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+
+    pub fn cell(&self, pos: Vec2) -> Option<&TerrainType> {
+        self.map.get(pos)
+    }
+
+    /// Neighbours at most one step higher than `pos`.
+    pub fn walkable_neighbors(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        let curr_ele = self.map[pos].heigh();
+
+        self.map
+            .neighbours4(pos)
+            .filter(move |&next| self.map[next].heigh() <= curr_ele + 1)
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.map.positions()
+    }
+
+    fn find(&self, terrain: &TerrainType) -> Option<Vec2> {
+        self.map.iter().find(|&(_, cell)| cell == terrain).map(|(pos, _)| pos)
+    }
+
+    fn lowest(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.map.iter().filter(|(_, cell)| cell.heigh() == 0).map(|(pos, _)| pos)
+    }
+
+    fn path_from(&self, starts: impl IntoIterator<Item = Vec2>) -> Option<Path<Vec2>> {
+        search::bfs_to(starts, |&pos| self.walkable_neighbors(pos), |&pos| self.map[pos] == TerrainType::Target)
+    }
+
+    /// Cells on a shortest path from `S` to `E`, both included.
+    pub fn shortest_path(&self) -> Option<Vec<Vec2>> {
+        Some(self.path_from(self.find(&TerrainType::Start))?.nodes)
+    }
+
+    /// Fewest steps from `S` to `E`.
+    pub fn steps_from_start(&self) -> Option<usize> {
+        Some(self.path_from(self.find(&TerrainType::Start))?.cost)
+    }
+
+    /// Fewest steps to `E` from any cell at the lowest elevation.
+    pub fn steps_from_lowest(&self) -> Option<usize> {
+        Some(self.path_from(self.lowest())?.cost)
     }
 }
 
-struct CellRecord {
-    prev: Option<GridCoord>,
+// the breadth first search spreading from the starts, one step further each time
+struct Climb {
+    grid: Grid,
+    reached: Paths<Vec2>,
+    target: Option<usize>,
+    steps: usize,
 }
 
-pub struct Grid {
-    data: Vec<TerrainType>,
-    height: usize,
-    width: usize,
-    visited: HashMap<GridCoord, CellRecord>,
-    current: HashSet<GridCoord>,
-    num_steps: usize,
+impl Climb {
+    fn new(grid: Grid, starts: Vec<Vec2>) -> Self {
+        let reached = search::bfs(starts, |&pos| grid.walkable_neighbors(pos));
+        let target = grid.find(&TerrainType::Target).and_then(|pos| reached.distance(&pos));
+
+        Climb { grid, reached, target, steps: 0 }
+    }
 }
 
-impl Grid {
-    pub fn new(input: &str) -> Self {
-        let lines = input.lines().collect::<Vec<_>>();
-        let height = lines.len();
-        let width = lines[0].len();
-
-        let data = lines
-            .into_iter()
-            .flat_map(|s| s.chars())
-            .map(|c| TerrainType::from(c))
-            .collect();
-
-        Self {
-            data,
-            height,
-            width,
-            visited: Default::default(),
-            current: Default::default(),
-            num_steps: 0,
+impl Simulation for Climb {
+    fn step(&mut self) {
+        if !self.is_done() {
+            self.steps += 1;
         }
     }
 
-    fn cell(&self, coord: GridCoord) -> Option<&TerrainType> {
-        if !self.valid(coord) {
-            return None;
-        }
+    // the target is reached, or everything reachable is when it is not
+    fn is_done(&self) -> bool {
+        let last = self.target.or_else(|| self.reached.distances().values().copied().max());
 
-        self.data.get(coord.x + coord.y * self.width)
-    }
-
-    fn valid(&self, coord: GridCoord) -> bool {
-        coord.x < self.width && coord.y < self.height
-    }
-
-    fn walkable_neighbors(&self, coord: GridCoord) -> impl Iterator<Item = GridCoord> + '_ {
-        let curr_ele = self.cell(coord).unwrap().heigh();
-
-        let deltas = [[-1isize, 0], [1, 0], [0, -1], [0, 1]];
-
-        deltas
-            .into_iter()
-            .filter_map(move |[dx, dy]| {
-                Some(GridCoord {
-                    x: coord.x.checked_add_signed(dx)?,
-                    y: coord.y.checked_add_signed(dy)?,
-                })
-            })
-            .filter(|&coord| self.valid(coord))
-            .filter(move |&coord| {
-                let new_elev = self.cell(coord).unwrap().heigh();
-                new_elev <= curr_ele + 1
-            })
-    }
-
-    fn step(&mut self) -> bool {
-        if self.current.is_empty() {
-            // find start coordinate
-            let mut start_coord: Option<GridCoord> = None;
-            for y in 0..self.height {
-                for x in 0..self.width {
-                    let coord: GridCoord = (x, y).into();
-                    if let TerrainType::Start = self.cell(coord).unwrap() {
-                        start_coord = Some(coord);
-                        break;
-                    }
-                }
-            }
-            let start_coord = start_coord.unwrap();
-            self.current.insert(start_coord);
-            self.visited.insert(start_coord, CellRecord { prev: None });
-            return true;
-        }
+        last.is_none_or(|last| self.steps >= last)
+    }
 
-        let current = std::mem::take(&mut self.current);
-        let mut next = HashSet::new();
-        let mut visited = std::mem::take(&mut self.visited);
-
-        for curr in current {
-            for ncoord in self.walkable_neighbors(curr) {
-                if visited.contains_key(&ncoord) {
-                    // don't visit it again!
-                    continue;
-                }
-                visited.insert(ncoord, CellRecord { prev: Some(curr) });
-                next.insert(ncoord);
-            }
+    fn render(&self) -> String {
+        self.grid.map.render(|pos, cell| {
+            if self.reached.distance(&pos).is_some_and(|distance| distance <= self.steps) { '□' } else { cell.char() }
+        })
+    }
+
+    fn status(&self) -> String {
+        format!("{} steps", self.steps)
+    }
+
+    // elevations from dark green to white
+    fn palette(&self) -> Palette {
+        ('a'..='z').fold(Palette::default(), |palette, c| {
+            let height = (c as u8 - b'a') as f64 / 25.0;
+            palette.with(c, Rgb::mix(Rgb(20, 70, 30), Rgb(240, 240, 240), height))
+        })
+    }
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Grid;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    // `S` is among the lowest cells, so `E` being reachable from it answers both parts
+    fn parse(input: &str) -> Result<Self::Input> {
+        let grid = Grid::new(input)?;
+        if grid.steps_from_start().is_none() {
+            return Err(parse::expected("a path from `S` to `E`, climbing at most one a step"));
         }
-        self.current = next;
-        self.visited = visited;
-        self.num_steps += 1;
-        return self.current.len() > 0;
-    }
-
-
-    fn print(&self) {
-      print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-      for y in 0..self.height {
-        for x in 0..self.width {
-          let coords : GridCoord = (x,y).into();
-          if self.visited.contains_key(&coords) {
-              print!("□");
-          }else {
-            let d = self.cell((x,y).into()).unwrap();
-            match d {
-              TerrainType::Start => print!("S"),
-              TerrainType::Target => print!("E"),
-              TerrainType::Terrain(v) => print!("{}", (b'a' + *v as u8) as char),
-            }
-          }
+
+        Ok(grid)
+    }
+
+    fn part1(grid: &Self::Input) -> usize {
+        grid.steps_from_start().expect("checked in parse")
+    }
+
+    fn part2(grid: &Self::Input) -> usize {
+        grid.steps_from_lowest().expect("checked in parse")
+    }
+}
+
+impl Animated for Day12 {
+    fn simulation(grid: Self::Input, part: Part) -> Box<dyn Simulation> {
+        let starts = match part {
+            Part::One => grid.find(&TerrainType::Start).into_iter().collect(),
+            Part::Two => grid.lowest().collect(),
+        };
+
+        Box::new(Climb::new(grid, starts))
+    }
+}
+
+impl Generate for Day12 {
+    // random elevations around a path winding row by row from `S` and climbing by at most one a
+    // step, so `E` at its end can always be reached
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let width = 5 + rng.below(2 * size);
+        let height = 27usize.div_ceil(width) + rng.below(size + 1);
+        let length = 27 + rng.below(width * height - 26);
+
+        let mut rows: Vec<Vec<char>> = (0..height).map(|_| rng.word("abcdefghijklmnopqrstuvwxyz", width).chars().collect()).collect();
+        for step in 0..length {
+            let (y, along) = (step / width, step % width);
+            let x = if y % 2 == 0 { along } else { width - 1 - along };
+            rows[y][x] = match step {
+                0 => 'S',
+                _ if step == length - 1 => 'E',
+                _ => (b'a' + (step * 25 / (length - 1)) as u8) as char,
+            };
         }
-        println!("");
-      }
+
+        rows.into_iter().map(|row| row.into_iter().chain(['\n']).collect::<String>()).collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, thread::Thread};
+    use crate::input;
 
     use super::*;
 
     #[test]
-    fn adventofcodeday12_1() {
-        let mut explorer = Grid::new(include_str!("input.txt"));
+    fn adventofcode1() -> Result<()> {
+        let grid = Day12::parse(&input::real(12)?)?;
+        assert_eq!(423, Day12::part1(&grid));
 
-        while explorer.step() {
-          explorer.print();
-          std::thread::sleep(Duration::from_millis(100));
+        Ok(())
+    }
+
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let grid = Day12::parse(&input::real(12)?)?;
+        assert_eq!(416, Day12::part2(&grid));
+
+        Ok(())
+    }
+
+    #[test]
+    fn climb_stops_at_the_target() -> Result<()> {
+        let grid = Grid::new("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi")?;
+
+        for (part, steps) in [(Part::One, 31), (Part::Two, 29)] {
+            let mut climb = Day12::simulation(grid.clone(), part);
+            climb.run();
+            assert_eq!(format!("{steps} steps"), climb.status());
         }
+
+        let mut climb = Day12::simulation(grid, Part::One);
+        climb.step();
+        assert!(climb.render().starts_with("□□bqponm\n□bc"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn shortest_path() -> Result<()> {
+        let grid = Grid::new("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi")?;
+        let path = grid.shortest_path().unwrap();
+
+        assert_eq!(32, path.len());
+        assert_eq!(Some(&TerrainType::Start), grid.cell(path[0]));
+        assert_eq!(Some(&TerrainType::Target), grid.cell(path[31]));
+        assert!(path.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let err = Day12::parse("SbE\n").unwrap_err();
+
+        assert_eq!("expected a path from `S` to `E`, climbing at most one a step", err.to_string());
+    }
+
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day12>(40, 1..=8, |_, grid, &from_start, &from_lowest| {
+            let path = grid.shortest_path().unwrap();
+            let heights: Vec<usize> = path.iter().map(|&pos| grid.cell(pos).unwrap().heigh()).collect();
+
+            assert_eq!(from_start + 1, path.len());
+            assert!(path.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+            assert!(heights.windows(2).all(|step| step[1] <= step[0] + 1), "{heights:?}");
+            // `S` is one of the lowest cells
+            assert!(from_lowest <= from_start && from_start >= 25);
+            Ok(())
+        })
     }
 
     #[test]
     fn testing_terrain_type() {
         dbg!(TerrainType::Target.heigh());
-        dbg!(TerrainType::from('z').heigh());
+        dbg!(TerrainType::try_from('z').unwrap().heigh());
+        assert!(TerrainType::try_from('?').is_err());
     }
 }
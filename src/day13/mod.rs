@@ -1,11 +1,17 @@
-use std::{str::FromStr, fmt::Debug, cmp::Ordering::Equal};
+//! Day 13: Distress Signal.
 
+use std::{fmt::Debug, cmp::Ordering::Equal, str::FromStr};
+
+use eyre::{Report, Result, WrapErr};
 use serde::{Deserialize, Serialize};
 
+use crate::{generate::{Generate, Rng}, log::debug, parse::{self, OrExpected}, solution::Solution};
+
 
+/// A packet value, read from its JSON form and ordered by the puzzle's comparison rules.
 #[derive(Deserialize, Serialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
-enum Element {
+pub enum Element {
   Number(usize),
   List(Vec<Element>)
 }
@@ -13,35 +19,45 @@ enum Element {
 impl Element {
   fn to_slice(&self) -> Vec<&Element> {
     match self {
-        Element::List(vec) => vec.iter().collect::<Vec<_>>().clone(),
-        e => vec![&e]
+        Element::List(vec) => vec.iter().collect::<Vec<_>>(),
+        e => vec![e]
     }
   }
 }
 
+impl FromStr for Element {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(|e| {
+            parse::expected(format!("a packet such as `[1,[2,3]]`, invalid at column {}", e.column()))
+        })
+    }
+}
+
 impl PartialOrd<Element> for Element {
     fn partial_cmp(&self, other: &Element) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Element {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         match (self, other) {
-            (Element::Number(l), Element::Number(r)) => l.partial_cmp(r),
+            (Element::Number(l), Element::Number(r)) => l.cmp(r),
             (l, r) => {
               let l = l.to_slice();
               let r = r.to_slice();
               l.iter()
                 .zip(r.iter())
-                .map(|(&l,&r)| l.partial_cmp(r))
-                .find(|&r| r != Some(Equal))
-                .unwrap_or_else(|| l.len().partial_cmp(&r.len()))
+                .map(|(&l,&r)| l.cmp(r))
+                .find(|&r| r != Equal)
+                .unwrap_or_else(|| l.len().cmp(&r.len()))
             },
         }
     }
 }
 
-impl Ord for Element {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
 impl Debug for Element {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -52,82 +68,172 @@ impl Debug for Element {
 }
 
 
+/// A pair of packets.
 #[derive(Debug)]
-struct Signal {
-  left: Element,
-  right: Element
+pub struct Signal {
+  pub left: Element,
+  pub right: Element
 }
 
 impl Signal {
-  fn new(left: &str, right: &str) -> Self {
-
-    let left = serde_json::from_str(left).expect("Left not valid");
-    let right = serde_json::from_str(right).expect("Right not valid");
-
-    Self {
-      left,
-      right
-    }
-
+  pub fn in_order(&self) -> bool {
+    self.left < self.right
   }
 }
 
+fn packet((number, line): (usize, &str)) -> Result<Element> {
+  line.parse().wrap_err_with(|| parse::at_line(number, line))
+}
 
-#[cfg(test)]
-mod tests {
-    use itertools::Itertools;
 
-    use super::*;
+pub struct Day13;
 
+impl Solution for Day13 {
+  type Input = Vec<Signal>;
+  type Answer1 = usize;
+  type Answer2 = usize;
 
-  #[test]
-  fn adventofcode1() {
-    //let sig = Signal::new("[1,1,3,1,1]", "[1,1,5,1,1]");
+  fn parse(input: &str) -> Result<Self::Input> {
+    let mut lines = parse::numbered(input);
+    let mut signals = vec![];
 
-    let data: Vec<_> = include_str!("./input.txt")
-      .split("\n\n")
-      .map(|s| s.split("\n"))
-      .map(|mut s| Signal::new(s.next().unwrap(), s.next().unwrap()) )
-      .collect();
+    // pairs of packets separated by a blank line
+    while let Some((number, line)) = lines.next() {
+      let left = packet((number, line))?;
+      let right = packet(lines.next().or_expected(format!("a second packet after line {number}"))?)?;
+      signals.push(Signal { left, right });
+
+      if let Some((number, line)) = lines.next().filter(|(_, line)| !line.is_empty()) {
+        return Err(parse::expected_at(number, line, "a blank line between pairs"));
+      }
+    }
+
+    Ok(signals)
+  }
 
+  fn part1(data: &Self::Input) -> usize {
     let mut acc = 0;
     for (i, signal) in data.iter().enumerate() {
       let i = i + 1;
 
-      let comp = signal.left < signal.right;
-      println!("Comparing {:?} and {:?} -> left > right : {}", signal.left, signal.right, comp);
+      let comp = signal.in_order();
+      debug!("Comparing {:?} and {:?} -> left > right : {}", signal.left, signal.right, comp);
       if comp {
         acc += i;
       }
-      
+
+    }
+
+    acc
+  }
+
+  fn part2(data: &Self::Input) -> usize {
+    let mut data: Vec<&Element> = data
+      .iter()
+      .flat_map(|signal| [&signal.left, &signal.right])
+      .collect();
+
+    let div1: Element = "[[2]]".parse().unwrap();
+    let div2: Element = "[[6]]".parse().unwrap();
+
+    data.push(&div1);
+    data.push(&div2);
+
+    data.sort();
+
+    let div1_index = data.binary_search(&&div1).unwrap() + 1;
+    let div2_index = data.binary_search(&&div2).unwrap() + 1;
+
+    div1_index * div2_index
+  }
+}
+
+
+impl Element {
+  fn random(rng: &mut Rng, depth: usize) -> Self {
+    if depth == 0 || rng.one_in(3) {
+      return Element::Number(rng.below(11));
     }
 
+    Element::List((0..rng.below(5)).map(|_| Element::random(rng, depth - 1)).collect())
+  }
+}
+
+impl Generate for Day13 {
+  // no packet compares equal to the other one of its pair or to a divider, which would leave
+  // their order undecided
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let depth = 1 + size.min(5);
+    let dividers: [Element; 2] = ["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
+    let packet = |rng: &mut Rng, other: Option<&Element>| loop {
+      let packet = Element::List((0..1 + rng.below(5)).map(|_| Element::random(rng, depth)).collect());
+      if dividers.iter().chain(other).all(|other| packet.cmp(other) != Equal) {
+        break packet;
+      }
+    };
 
-    println!("{:?} are in correct order", acc);
+    let pairs: Vec<String> = (0..2 * size)
+      .map(|_| {
+        let left = packet(rng, None);
+        let right = packet(rng, Some(&left));
+
+        format!("{}\n{}\n", serde_json::to_string(&left).unwrap(), serde_json::to_string(&right).unwrap())
+      })
+      .collect();
+
+    pairs.join("\n")
   }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input;
+
+    use super::*;
+
 
   #[test]
-  fn adventofcode2() {
-    //let sig = Signal::new("[1,1,3,1,1]", "[1,1,5,1,1]");
+  fn adventofcode1() -> Result<()> {
+    let signals = Day13::parse(&input::real(13)?)?;
+    assert_eq!(5580, Day13::part1(&signals));
 
-    let mut data: Vec<Element> = include_str!("./input.txt")
-      .lines()
-      .filter(|s| !s.is_empty())
-      .map(|s| serde_json::from_str(s).unwrap())
-      .collect();
+    Ok(())
+  }
+
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let signals = Day13::parse(&input::real(13)?)?;
+    assert_eq!(26200, Day13::part2(&signals));
 
-    let div1: Element = serde_json::from_str("[[2]]").unwrap();
-    let div2: Element = serde_json::from_str("[[6]]").unwrap();
+    Ok(())
+  }
 
-    data.push(div1.clone());
-    data.push(div2.clone());
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    use std::cmp::Ordering;
 
-    data.sort();
-    
-    let div1_index = data.binary_search(&div1).unwrap() + 1;
-    let div2_index = data.binary_search(&div2).unwrap() + 1;
+    use serde_json::{json, Value};
 
-    dbg!(div1_index * div2_index);
+    // the comparison rules straight from the puzzle, on plain JSON
+    fn compare(left: &Value, right: &Value) -> Ordering {
+      match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_u64().cmp(&r.as_u64()),
+        (Value::Array(l), Value::Array(r)) => {
+          l.iter().zip(r).map(|(l, r)| compare(l, r)).find(|&o| o != Ordering::Equal).unwrap_or(l.len().cmp(&r.len()))
+        }
+        (Value::Number(_), _) => compare(&json!([left]), right),
+        (_, _) => compare(left, &json!([right])),
+      }
+    }
 
+    crate::generate::check::<Day13>(40, 1..=6, |text, _, &ordered, &key| {
+      let packets: Vec<Value> = text.lines().filter(|line| !line.is_empty()).map(|line| serde_json::from_str(line).unwrap()).collect();
+      let in_order: usize = packets.chunks(2).enumerate().filter(|(_, pair)| compare(&pair[0], &pair[1]).is_lt()).map(|(i, _)| i + 1).sum();
+      let before = |divider: Value| packets.iter().filter(|p| compare(p, &divider).is_lt()).count();
+
+      assert_eq!(in_order, ordered);
+      assert_eq!((before(json!([[2]])) + 1) * (before(json!([[6]])) + 2), key);
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
@@ -1,94 +1,71 @@
-use std::{collections::HashSet, fmt::Debug, str::FromStr};
-
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct Coord {
-    x: usize,
-    y: usize,
-}
-
-impl Coord {
-    fn path_to(&self, other: &Coord) -> Vec<Coord> {
-        let mut res = vec![];
-
-        if self.y == other.y {
-            for x in self.x.min(other.x)..self.x.max(other.x) + 1 {
-                res.push(Coord { x, y: self.y });
-            }
-        }
-
-        if self.x == other.x {
-            for y in self.y.min(other.y)..self.y.max(other.y) + 1 {
-                res.push(Coord { y, x: self.x });
-            }
-        }
-
-        res
+//! Day 14: Regolith Reservoir.
+
+use std::{collections::HashSet, str::FromStr};
+
+use eyre::{Report, Result};
+
+use crate::{
+    generate::{Generate, Rng},
+    grid::{CellChar, Grid},
+    log::debug,
+    parse,
+    point::Vec2,
+    simulation::{Animated, Simulation},
+    solution::{Part, Solution},
+};
+
+// the cells of a horizontal or vertical rock line, both ends included
+fn line(from: Vec2, to: Vec2) -> Vec<Vec2> {
+    let step = (to - from).signum();
+    let mut res = vec![from];
+    let mut curr = from;
+    while curr != to {
+        curr += step;
+        res.push(curr);
     }
 
-    fn get_next(&self) -> [Coord; 3] {
-        [
-            (self.x, self.y + 1).into(),
-            (self.x - 1, self.y + 1).into(),
-            (self.x + 1, self.y + 1).into(),
-        ]
-    }
-}
-
-impl FromStr for Coord {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coord: Vec<usize> = s
-            .split(",")
-            .filter_map(|s| s.parse::<usize>().ok())
-            .collect();
-
-        assert!(coord.len() == 2);
-
-        Ok(Coord {
-            x: coord[0],
-            y: coord[1],
-        })
-    }
+    res
 }
 
-impl From<(usize, usize)> for Coord {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
+// where a grain of sand tries to go next, in order
+fn next_positions(pos: Vec2) -> [Vec2; 3] {
+    [pos + Vec2::DOWN, pos + Vec2::DOWN + Vec2::LEFT, pos + Vec2::DOWN + Vec2::RIGHT]
 }
 
 #[derive(Debug)]
 struct RockPath {
-    coords: Vec<Coord>,
+    coords: Vec<Vec2>,
 }
 
 impl RockPath {
-    fn all_coords(&self) -> HashSet<Coord> {
+    fn all_coords(&self) -> HashSet<Vec2> {
         self.coords
             .windows(2)
-            .flat_map(|win| win[0].path_to(&win[1]))
+            .flat_map(|win| line(win[0], win[1]))
             .collect()
     }
 }
 
-impl FromIterator<Coord> for RockPath {
-    fn from_iter<T: IntoIterator<Item = Coord>>(iter: T) -> Self {
-        let coords: Vec<_> = iter.into_iter().collect();
-        coords.into()
+impl FromStr for RockPath {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let coords = s.split(" -> ").map(Vec2::from_str).collect::<Result<Vec<_>>>()?;
+        if let Some(win) = coords.windows(2).find(|win| win[0].x != win[1].x && win[0].y != win[1].y) {
+            return Err(parse::expected(format!("horizontal or vertical lines, found {} -> {}", win[0], win[1])));
+        }
+
+        Ok(coords.into())
     }
 }
 
-impl From<Vec<Coord>> for RockPath {
-    fn from(coords: Vec<Coord>) -> Self {
+impl From<Vec<Vec2>> for RockPath {
+    fn from(coords: Vec<Vec2>) -> Self {
         RockPath { coords }
     }
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 enum Element {
     Sand,
     Rock,
@@ -96,179 +73,238 @@ enum Element {
     Source,
 }
 
-impl Debug for Element {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl CellChar for Element {
+    fn char(&self) -> char {
         match self {
-            Self::Sand => write!(f, "o"),
-            Self::Rock => write!(f, "#"),
-            Self::Air => write!(f, "."),
-            Self::Source => write!(f, "+"),
+            Self::Sand => 'o',
+            Self::Rock => '#',
+            Self::Air => '.',
+            Self::Source => '+',
         }
     }
 }
 
-struct Grid {
-    data: Vec<Element>,
-    x_min: usize,
-    x_max: usize,
-    y_max: usize,
-    curr: Option<Coord>,
+const SOURCE: Vec2 = Vec2::new(500, 0);
+
+struct Cave {
+    cells: Grid<Element>,
+    curr: Option<Vec2>,
+    floor: bool,
+    done: bool,
 }
 
-impl Debug for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.heigh() {
-            for e in self.data[y * self.width()..y * self.width() + self.width()].iter() {
-                write!(f, "{:?} ", e)?;
-            }
-            writeln!(f, "")?;
+impl Cave {
+    fn new(input: HashSet<Vec2>, x_min: isize, x_max: isize, y_max: isize, floor: bool) -> Self {
+        let mut cells = Grid::new((1 + x_max - x_min) as usize, (y_max + 1) as usize, Element::Air)
+            .with_origin(Vec2::new(x_min, 0));
+
+        for &rock in &input {
+            cells[rock] = Element::Rock;
+        }
+        if let Some(source) = cells.get_mut(SOURCE) {
+            *source = Element::Source;
         }
 
-        Ok(())
+        Cave {
+            cells,
+            curr: Default::default(),
+            floor,
+            done: false,
+        }
+    }
+
+    fn sand(&self) -> usize {
+        self.cells.values().filter(|&e| e == &Element::Sand).count()
     }
 }
 
-impl Grid {
-    fn new(input: HashSet<Coord>, x_min: usize, x_max: usize, y_max: usize) -> Self {
-        let mut data = vec![];
-
-        for y in 0..=y_max {
-            for x in x_min..=x_max {
-                if x == 500 && y == 0 {
-                    data.push(Element::Source);
-                } else if input.contains(&(x, y).into()) {
-                    data.push(Element::Rock);
-                } else {
-                    data.push(Element::Air);
+impl Simulation for Cave {
+    // moves the falling grain by one cell, or drops a new one from the source
+    fn step(&mut self) {
+        if self.done {
+            return;
+        }
+        let curr = self.curr.take().unwrap_or(SOURCE);
+
+        for possible_next in next_positions(curr) {
+            match self.cells.get(possible_next) {
+                None => {
+                  if !self.floor {
+                    // falling out of the grid -> into the abyss
+                    self.done = true;
+                    return;
+                  }
+                  self.cells[curr] = Element::Sand;
+
+                  return;
+                },
+                Some(Element::Air) => {
+                    self.curr = Some(possible_next);
+                    return;
                 }
+                Some(Element::Sand | Element::Rock) => (),
+                Some(Element::Source) => unreachable!(),
             }
         }
 
-        Grid {
-            x_min,
-            x_max,
-            y_max,
-            data,
-            curr: Default::default(),
-        }
-    }
+        //can go anywhere -> become at rest
+        self.cells[curr] = Element::Sand;
 
-    fn cell(&self, coord: &Coord) -> Result<&Element, ()> {
-        if coord.x < self.x_min || coord.x > self.x_max || coord.y > self.y_max {
-            return Err(());
+        if curr == SOURCE {
+          self.done = true;
         }
+    }
 
-        return Ok(&self.data[(coord.x - self.x_min) + coord.y * self.width()]);
+    fn is_done(&self) -> bool {
+        self.done
     }
 
-    fn cell_mut(&mut self, coord: &Coord) -> Result<&mut Element, ()> {
-        if coord.x < self.x_min || coord.x > self.x_max || coord.y > self.y_max {
-            return Err(());
+    // only the columns sand can reach, the pile never being wider than the cave is deep
+    fn render(&self) -> String {
+        let reach = self.cells.height() as isize;
+        let x_range = self.cells.x_range();
+        let columns = x_range.start.max(SOURCE.x - reach)..x_range.end.min(SOURCE.x + reach + 1);
+
+        let mut out = String::new();
+        for y in self.cells.y_range() {
+            for x in columns.clone() {
+                let pos = Vec2::new(x, y);
+                out.push(if self.curr == Some(pos) { '~' } else { self.cells[pos].char() });
+            }
+            out.push('\n');
         }
 
-        let width = self.width();
-        return Ok(&mut self.data[(coord.x - self.x_min) + coord.y * width]);
+        out
     }
 
-    fn width(&self) -> usize {
-        1 + self.x_max - self.x_min
+    fn status(&self) -> String {
+        format!("{} grains of sand at rest", self.sand())
     }
+}
 
-    fn heigh(&self) -> usize {
-        self.y_max + 1
-    }
+fn fill(mut cave: Cave) -> usize {
+    cave.run();
+    debug!("{}", cave.cells);
+
+    cave.sand()
+}
 
-    fn step(&mut self) -> Result<Status, ()> {
-        if self.curr == None {
-            self.curr = Some((500, 0).into());
+// the cave of a part, its bottom rows and columns sized to hold all the sand
+fn cave(rocks: &HashSet<Vec2>, part: Part) -> Cave {
+    match part {
+        Part::One => {
+            let min_x = rocks.iter().map(|c| c.x).min().unwrap() - 1;
+            let max_x = rocks.iter().map(|c| c.x).max().unwrap() + 1;
+            let max_y = rocks.iter().map(|c| c.y).max().unwrap();
+
+            Cave::new(rocks.clone(), min_x, max_x, max_y, false)
         }
+        Part::Two => {
+            let mut rocks = rocks.clone();
 
-        let curr = self.curr.take().unwrap();
+            let min_x = 0;
+            let max_x = rocks.iter().map(|c| c.x).max().unwrap() * 2;
 
-        for possible_next in curr.get_next() {
-            let val = self.cell(&possible_next);
+            let max_y = rocks.iter().map(|c| c.y).max().unwrap() + 2;
 
-            match val {
-                Err(_) => {
-                  *self.cell_mut(&curr).unwrap() = Element::Sand;
-                  self.curr = None;
-          
-                  return Ok(Status::Resting);
-                },
-                Ok(val) => match val {
-                    Element::Air => {
-                        self.curr = Some(possible_next);
-                        return Ok(Status::Falling);
-                    }
-                    Element::Sand => (),
-                    Element::Rock => (),
-                    Element::Source => unreachable!(),
-                },
-            }
+            let bottom: RockPath = vec![Vec2::new(min_x, max_y), Vec2::new(max_x, max_y)].into();
+            rocks.extend(bottom.all_coords());
+
+            Cave::new(rocks, min_x, max_x, max_y, true)
         }
+    }
+}
 
-        //can go anywhere -> become at rest
-        *self.cell_mut(&curr).unwrap() = Element::Sand;
+pub struct Day14;
 
-        if curr == (500, 0).into() {
-          return Err(());
-        }
+impl Solution for Day14 {
+    type Input = HashSet<Vec2>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let paths = parse::each_line(input, RockPath::from_str)?;
 
-        self.curr = None;
-        Ok(Status::Resting)
+        Ok(paths.iter().flat_map(|rp| rp.all_coords()).collect())
+    }
+
+    fn part1(rocks: &Self::Input) -> usize {
+        fill(cave(rocks, Part::One))
+    }
+
+    fn part2(rocks: &Self::Input) -> usize {
+        fill(cave(rocks, Part::Two))
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum Status {
-    Falling,
-    Resting,
+impl Animated for Day14 {
+    fn simulation(rocks: Self::Input, part: Part) -> Box<dyn Simulation> {
+        Box::new(cave(&rocks, part))
+    }
+}
+
+impl Generate for Day14 {
+    // the first ledge sits under the source, so the cave is wide enough to pour into
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let spread = 4 + 2 * size as isize;
+        let mut out = String::new();
+        for path in 0..size {
+            let mut at = match path {
+                0 => Vec2::new(500 - rng.range(1..=5), rng.range(2..=spread)),
+                _ => Vec2::new(500 + rng.range(-spread..=spread), rng.range(2..=spread)),
+            };
+            let mut points = vec![at];
+            for segment in 0..1 + rng.below(4) {
+                let length = rng.range(1..=6);
+                at += match (segment % 2 == 0, path == 0 || rng.one_in(2)) {
+                    (true, _) if path == 0 && segment == 0 => Vec2::new(length + 5, 0),
+                    (true, true) => Vec2::new(length, 0),
+                    (true, false) => Vec2::new(-length, 0),
+                    (false, true) => Vec2::new(0, length),
+                    // rock stays below the source's row
+                    (false, false) => Vec2::new(0, -length.min(at.y - 1)),
+                };
+                points.push(at);
+            }
+            let points: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+            out += &format!("{}\n", points.join(" -> "));
+        }
+
+        out
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
+    use crate::input;
+
     use super::*;
 
     #[test]
-    fn adventofcode1() {
-        let mut data: HashSet<Coord> = include_str!("input.txt")
-            .lines()
-            .map(|s| {
-                s.split(" -> ")
-                    .map(|s| Coord::from_str(s).unwrap())
-                    .collect()
-            })
-            .flat_map(|rp: RockPath| rp.all_coords())
-            .collect();
-
-        let min_x =  0; // data.iter().map(|c| c.x).min().unwrap();
-        let max_x =  data.iter().map(|c| c.x).max().unwrap() * 2;
-
-        let max_y = data.iter().map(|c| c.y).max().unwrap() + 2;
-
-        let bottom: RockPath = vec![(min_x, max_y).into(), (max_x, max_y).into()].into();
-        data.extend(bottom.all_coords().into_iter());
-
-        let mut grid = Grid::new(data, min_x, max_x, max_y);
-
-        //println!("{:?}", grid);
-        while let Ok(val) = grid.step() {
-            //println!("{:?}", val);
-            if val == Status::Resting {
-                //println!("{:?}", grid);
-            }
-        }
-        println!("{:?}", grid);
+    fn adventofcode1() -> Result<()> {
+        let rocks = Day14::parse(&input::real(14)?)?;
+        assert_eq!(698, Day14::part1(&rocks));
 
-        let nbr_sand = grid
-            .data
-            .iter()
-            .filter(|&e| e == &Element::Sand)
-            .collect::<Vec<_>>()
-            .len();
-        dbg!(nbr_sand);
+        Ok(())
+    }
+
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let rocks = Day14::parse(&input::real(14)?)?;
+        assert_eq!(28594, Day14::part2(&rocks));
 
-        dbg!(min_x, max_x, max_y);
+        Ok(())
+    }
+
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day14>(30, 1..=8, |_, rocks, &into_abyss, &onto_floor| {
+            // with a floor, sand piles up in a triangle with a row of 2y + 1 cells at each depth y
+            let floor = rocks.iter().map(|rock| rock.y).max().unwrap() as usize + 2;
+
+            assert!(into_abyss <= onto_floor && onto_floor <= floor * floor, "{into_abyss} {onto_floor} {floor}");
+            Ok(())
+        })
     }
 }
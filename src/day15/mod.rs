@@ -1,294 +1,327 @@
-use std::{fmt::Debug, collections::btree_map::Range, ops::RangeInclusive};
+//! Day 15: Beacon Exclusion Zone.
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone)]
-struct Point {
-    x: isize,
-    y: isize,
-}
+use std::ops::RangeInclusive;
 
-impl Point {
-    fn dist(&self, other: &Point) -> usize {
-        let x_dist = self.x.abs_diff(other.x);
-        let y_dist = self.y.abs_diff(other.y);
+use eyre::{bail, eyre, Report, Result};
+use rayon::prelude::*;
 
-        x_dist + y_dist
-    }
-}
-
-impl Into<Point> for (usize, usize) {
-    fn into(self) -> Point {
-        Point {
-            x: self.0 as isize,
-            y: self.1 as isize,
-        }
-    }
-}
-
-impl Into<Point> for &str {
-    fn into(self) -> Point {
-        let mut splits = self.split(", ");
-        let x = &splits.next().unwrap()["x=".len()..];
-        let y = &splits.next().unwrap()["y=".len()..];
-
-        Point {
-            x: x.parse().unwrap(),
-            y: y.parse().unwrap(),
-        }
-    }
-}
+use crate::{crosscheck::{Implementation, Variants}, generate::{Generate, Rng}, grid::Grid, input::Params, interval::IntervalSet, log::debug, parse, point::Vec2, solution::Solution};
 
+/// A sensor and the closest beacon it reports, which no other beacon can be closer than.
 #[derive(Debug)]
-struct Sensor {
-    pos: Point,
+pub struct Sensor {
+    pos: Vec2,
     beacon: Beacon,
     radius: usize,
 }
 
 impl Sensor {
-  fn y_range(&self) -> RangeInclusive<isize> {
+  pub fn new(pos: Vec2, beacon: Vec2) -> Self {
+    let radius = pos.manhattan(beacon);
+    Sensor { pos, beacon: Beacon { pos: beacon }, radius }
+  }
+
+  pub fn position(&self) -> Vec2 {
+    self.pos
+  }
+
+  pub fn beacon(&self) -> Vec2 {
+    self.beacon.pos
+  }
+
+  /// Rows the sensor rules beacons out of.
+  pub fn y_range(&self) -> RangeInclusive<isize> {
     self.pos.y - self.radius as isize..=self.pos.y + self.radius as isize
   }
+
+  /// Columns the sensor rules beacons out of on row `y`.
+  pub fn x_range_at(&self, y: isize) -> Option<RangeInclusive<isize>> {
+    if !self.y_range().contains(&y) {
+      return None;
+    }
+
+    let dist_to_y = (self.pos.y - y).abs();
+    let left_distance = self.radius as isize - dist_to_y;
+    Some((self.pos.x - left_distance)..=(self.pos.x + left_distance))
+  }
 }
 
 #[derive(Debug)]
 struct Beacon {
-    pos: Point,
+    pos: Vec2,
 }
 
-#[derive(Hash, PartialEq, Eq, Clone)]
-enum Type {
-    Sensor,
-    Beacon,
-    Unknown,
-    Impossible,
+fn sensor(line: &str) -> Result<Sensor> {
+    let [sensor, beacon] = parse::line(line, parse::template("Sensor at {}: closest beacon is at {}", parse::vec2))?;
+
+    Ok(Sensor::new(sensor, beacon))
 }
 
-impl Debug for Type {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Sensor => write!(f, "S"),
-            Self::Beacon => write!(f, "B"),
-            Self::Unknown => write!(f, "."),
-            Self::Impossible => write!(f, "#"),
-        }
-    }
+/// The sensors, with the row part 1 counts on and the largest coordinate part 2 searches up to.
+#[derive(Debug)]
+pub struct Scan {
+    pub sensors: Vec<Sensor>,
+    pub row: isize,
+    pub max: isize,
 }
 
-trait ParseLine {
-    fn read(&self) -> Sensor;
+// every cell of the search area is covered
+fn no_free_cell(max: isize) -> Report {
+    eyre!("no free cell with both coordinates between 0 and {max}")
 }
 
-impl ParseLine for &str {
-    fn read(&self) -> Sensor {
-        let mut splits = self.split(":");
+pub struct Day15;
 
-        let sensor_pos: Point = splits.next().unwrap()["Sensor at ".len()..].into();
-        let beacon_pos: Point = splits.next().unwrap()[" closest beacon is at ".len()..].into();
+impl Solution for Day15 {
+    type Input = Scan;
+    type Answer1 = isize;
+    type Answer2 = Result<isize>;
 
-        let radius = sensor_pos.dist(&beacon_pos);
+    // the row and search area the real puzzle asks about
+    fn parse(input: &str) -> Result<Self::Input> {
+        let sensors = parse::each_line(input, sensor)?;
 
-        Sensor {
-            pos: sensor_pos,
-            beacon: Beacon { pos: beacon_pos },
-            radius: radius,
-        }
-    }
-}
+        debug!("Data parsed");
 
-struct State {
-    size: usize,
-    state: Vec<Type>,
-}
+        Ok(Scan { sensors, row: 2_000_000, max: 4_000_000 })
+    }
 
-impl Debug for State {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for j in 0..self.size {
-            for i in 0..self.size {
-                write!(f, "{:?}", self.cell(&(i, j).into()).unwrap())?;
+    // the example asks about row 10 and a search area up to 20 instead
+    fn parse_with(input: &str, params: &Params) -> Result<Self::Input> {
+        let mut scan = Self::parse(input)?;
+        for (name, &value) in params {
+            match name.as_str() {
+                "row" => scan.row = value,
+                "max" => scan.max = value,
+                _ => bail!("unknown parameter {name:?}, day 15 takes `row` and `max`"),
             }
-            writeln!(f, "")?;
         }
-        write!(f, "")
-    }
-}
 
-impl State {
-    fn new(size: usize) -> Self {
-        Self {
-            size,
-            state: vec![Type::Unknown; size * size],
-        }
+        Ok(scan)
     }
 
-    fn cell(&self, p: &Point) -> Option<&Type> {
-        if p.x < 0 || p.x > self.size as isize || p.y < 0 || p.y > self.size as isize {
-            return None;
-        }
+    fn part1(scan: &Self::Input) -> isize {
+        let (sensors, target_y) = (&scan.sensors, scan.row);
+
+        let covered: IntervalSet<isize> = sensors
+            .iter()
+            .filter_map(|sensor| sensor.x_range_at(target_y))
+            .collect();
 
-        let x = p.x as usize;
-        let y = p.y as usize;
-        self.state.get(x + y * self.size)
+        let mut beacons: Vec<isize> = sensors
+            .iter()
+            .filter(|sensor| sensor.beacon.pos.y == target_y)
+            .map(|sensor| sensor.beacon.pos.x)
+            .collect();
+        beacons.sort();
+        beacons.dedup();
+
+        covered.len() as isize - beacons.len() as isize
     }
 
-    fn cell_mut(&mut self, p: &Point) -> Option<&mut Type> {
-        if p.x < 0 || p.x > self.size as isize || p.y < 0 || p.y > self.size as isize {
-            return None;
+    fn part2(scan: &Self::Input) -> Result<isize> {
+        let (sensors, max_y) = (&scan.sensors, scan.max);
+
+        for j in 0..=max_y {
+            if let Some(x) = free_in_row(sensors, j, max_y) {
+              debug!("It's here ? : x:{x} y:{j}");
+              return Ok(x * 4_000_000 + j);
+            }
         }
 
-        let x = p.x as usize;
-        let y = p.y as usize;
-        self.state.get_mut(x + y * self.size)
+        Err(no_free_cell(max_y))
     }
 }
 
-struct Ranges{
-  ranges: Vec<RangeInclusive<isize>>
+// the first column of row `y` between 0 and `max` no sensor covers
+fn free_in_row(sensors: &[Sensor], y: isize, max: isize) -> Option<isize> {
+    let mut free = IntervalSet::from(0..=max);
+    for sensor in sensors.iter() {
+      if let Some(range_to_sub) = sensor.x_range_at(y) {
+        free.remove(range_to_sub);
+      }
+    }
+
+    debug!("{:?}", free);
+
+    free.bounds().map(|bounds| *bounds.start())
 }
 
-impl Ranges {
-  fn new(y_max: isize) -> Self {
-    Self {
-      ranges: vec![0..=y_max]
-    }
-  }
+// every cell of the row, one at a time
+fn ruled_out_cells(scan: &Scan) -> isize {
+    let (sensors, target_y) = (&scan.sensors, scan.row);
+    let start = sensors.iter().map(|sensor| sensor.pos.x - sensor.radius as isize).min().unwrap_or_default();
+    let end = sensors.iter().map(|sensor| sensor.pos.x + sensor.radius as isize).max().unwrap_or_default();
+
+    (start..=end)
+        .map(|x| Vec2::new(x, target_y))
+        .filter(|&pos| sensors.iter().any(|sensor| sensor.pos.manhattan(pos) <= sensor.radius))
+        .filter(|&pos| sensors.iter().all(|sensor| sensor.beacon.pos != pos))
+        .count() as isize
+}
 
-  fn minus(&mut self, minus: &RangeInclusive<isize>) {
+// the rows split between threads
+fn hidden_beacon_parallel(scan: &Scan) -> Result<isize> {
+    let (sensors, max_y) = (&scan.sensors, scan.max);
 
-    self.ranges = self.ranges.iter().flat_map(|left| Ranges::range_ops(left, minus)).collect();
+    (0..=max_y)
+        .into_par_iter()
+        .find_map_first(|y| free_in_row(sensors, y, max_y).map(|x| x * 4_000_000 + y))
+        .ok_or_else(|| no_free_cell(max_y))
+}
 
-  }
+// a single cell is left uncovered, so it lies just out of some sensor's reach
+fn hidden_beacon_perimeter(scan: &Scan) -> Result<isize> {
+    let (sensors, max) = (&scan.sensors, scan.max);
+
+    sensors
+        .iter()
+        .flat_map(|sensor| {
+            let reach = sensor.radius as isize + 1;
+            (0..=reach).flat_map(move |dx| {
+                let dy = reach - dx;
+                [(dx, dy), (-dx, dy), (dx, -dy), (-dx, -dy)].map(|(dx, dy)| sensor.pos + Vec2::new(dx, dy))
+            })
+        })
+        .filter(|pos| (0..=max).contains(&pos.x) && (0..=max).contains(&pos.y))
+        .find(|&pos| sensors.iter().all(|sensor| sensor.pos.manhattan(pos) > sensor.radius))
+        .map(|pos| pos.x * 4_000_000 + pos.y)
+        .ok_or_else(|| no_free_cell(max))
+}
 
-  fn range_ops(left: &RangeInclusive<isize>, right: &RangeInclusive<isize>) -> Vec<RangeInclusive<isize>> {
-    if left.start() > right.end() || left.end() < right.start() {
-      return vec![left.clone()];
-    }
+impl Variants for Day15 {
+    const PART1: &'static [Implementation<Scan, isize>] =
+        &[("intervals", Day15::part1), ("cells", ruled_out_cells)];
+    const PART2: &'static [Implementation<Scan, Result<isize>>] = &[
+        ("rows", Day15::part2),
+        ("parallel", hidden_beacon_parallel),
+        ("perimeter", hidden_beacon_perimeter),
+    ];
+}
 
-    if right.contains(left.start()) && right.contains(left.end()) {
-      return vec![];
-    }
+// the side of the generated search area, as small as the example's
+const SIDE: isize = 21;
+
+impl Generate for Day15 {
+    // sensors being added until only one hidden cell is left uncovered
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let hidden = Vec2::new(rng.range(0..=SIDE - 1), rng.range(0..=SIDE - 1));
+        let mut covered = Grid::new(SIDE as usize, SIDE as usize, false);
+        *covered.get_mut(hidden).unwrap() = true;
+
+        let spread = size as isize;
+        let mut out = String::new();
+        loop {
+            let uncovered: Vec<Vec2> = covered.positions().filter(|&pos| !covered.get(pos).unwrap()).collect();
+            if uncovered.is_empty() {
+                break;
+            }
+            let cell = *rng.pick(&uncovered);
+
+            // the sensor reaches the cell but stops just short of the hidden one
+            let nearby = cell + Vec2::new(rng.range(-spread..=spread), rng.range(-spread..=spread));
+            let pos = if nearby.manhattan(cell) < nearby.manhattan(hidden) && nearby.manhattan(hidden) > 1 {
+                nearby
+            } else {
+                let away = hidden - cell;
+                cell + if away.x.abs() > away.y.abs() { Vec2::new(-away.x.signum(), 0) } else { Vec2::new(0, -away.y.signum()) }
+            };
+            let radius = pos.manhattan(hidden) as isize - 1;
+            let dx = rng.range(-radius..=radius);
+            let dy = (radius - dx.abs()) * if rng.one_in(2) { 1 } else { -1 };
+            let beacon = pos + Vec2::new(dx, dy);
+
+            for cell in covered.positions().filter(|&cell| cell.manhattan(pos) as isize <= radius).collect::<Vec<_>>() {
+                *covered.get_mut(cell).unwrap() = true;
+            }
+            out += &format!("Sensor at x={}, y={}: closest beacon is at x={}, y={}\n", pos.x, pos.y, beacon.x, beacon.y);
+        }
 
-    if left.start() < right.start() && left.end() > right.end() {
-      return vec![*left.start()..=*right.start()-1, *right.end()+1..=*left.end()];
-    }
-    
-    if left.contains(&(right.end() + 1)) {
-      return vec![*right.end()+1..=*left.end()];
+        out
     }
 
-    if left.contains(&(right.start() - 1)) {
-      return vec![*left.start()..=*right.start()-1];
+    fn params() -> Params {
+        Params::from([("row".to_owned(), 10), ("max".to_owned(), SIDE - 1)])
     }
-
-    println!("doing {:?} - {:?}", left, right);
-    unreachable!()
-  }
- }
+}
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use crate::input;
 
     use super::*;
 
-    // #[test]
-    // fn adventofcode1() {
-    //     let data: Vec<_> = include_str!("input.txt")
-    //         .lines()
-    //         .map(|s| s.read())
-    //         .collect();
-
-    //     let mut all_points: HashSet<Point> = HashSet::new();
-    //     let mut sensor_beacon = HashSet::new();
-
-    //     let target_y = 2000000;
-
-    //     for (s, b) in data.iter() {
-    //       let dist = s.0.dist(&b.0);
-    //       //println!("{s:?} - {b:?} : {dist}");
-
-    //       sensor_beacon.insert(s.0.clone());
-    //       sensor_beacon.insert(b.0.clone());
-
-    //       let closest = Point {
-    //         x: s.0.x,
-    //         y: target_y
-    //       };
-
-    //       let dist_to_y_target = closest.dist(&s.0);
+    #[test]
+    fn adventofcode1() -> Result<()> {
+        let scan = Day15::parse(&input::real(15)?)?;
+        assert_eq!(5240818, Day15::part1(&scan));
 
-    //       if dist_to_y_target > dist {
-    //         continue;
-    //       }
+        Ok(())
+    }
 
-    //       let move_left = (dist - dist_to_y_target) as isize;
-    //       //println!("{s:?} -> {move_left}");
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let scan = Day15::parse(&input::real(15)?)?;
+        assert_eq!(13213086906101, Day15::part2(&scan)?);
 
-    //       for x in s.0.x-move_left..=s.0.x+move_left {
-    //         let point = Point {
-    //             x,
-    //             y: target_y
-    //           };
+        Ok(())
+    }
 
-    //         if !sensor_beacon.contains(&point) {
-    //           all_points.insert(
-    //             point
-    //           );
-    //         }
-    //       }
-    //     }
-    //     dbg!(all_points.len());
-    // }
 
     #[test]
-    fn adventofcode2() {
-        let sensors: Vec<_> = include_str!("input.txt")
-            .lines()
-            .map(|s| s.read())
-            .collect();
-
-        println!("Data parsed");
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day15>(30, 1..=8, |_, scan, &ruled_out, frequency| {
+            let sensors = &scan.sensors;
+            let covers = |pos: Vec2| sensors.iter().any(|sensor| sensor.position().manhattan(pos) <= sensor.radius);
+            let row: Vec<Vec2> = (-200..=200).map(|x| Vec2::new(x, 10)).collect();
+            let hidden: Vec<Vec2> = (0..=20).flat_map(|y| (0..=20).map(move |x| Vec2::new(x, y))).filter(|&pos| !covers(pos)).collect();
+
+            assert_eq!((10, 20), (scan.row, scan.max));
+            assert_eq!(row.iter().filter(|&&pos| covers(pos) && sensors.iter().all(|s| s.beacon() != pos)).count() as isize, ruled_out);
+            assert_eq!(1, hidden.len());
+            assert_eq!(hidden[0].x * 4_000_000 + hidden[0].y, *frequency.as_ref().unwrap());
+            Ok(())
+        })
+    }
 
-        let max_y = 4_000_000;
+    #[test]
+    fn fixture_params() -> Result<()> {
+        let sensor = "Sensor at x=8, y=7: closest beacon is at x=2, y=10\n";
 
-        for j in 0..=max_y {
-            let mut ranges = Ranges::new(max_y);
-            //println!("For line {j}");
-            for sensor in sensors.iter() {
-              if !sensor.y_range().contains(&j) {
-                continue;
-              }
+        let scan = Day15::parse_with(sensor, &Day15::params())?;
+        assert_eq!((10, 20, 1), (scan.row, scan.max, scan.sensors.len()));
+        let scan = Day15::parse(sensor)?;
+        assert_eq!((2_000_000, 4_000_000), (scan.row, scan.max));
 
-              //println!("Sensor in range {:?} (r: {})", sensor.pos, sensor.radius);
-              let dist_to_j = (sensor.pos.y - j ).abs();
-              let left_distance =  sensor.radius as isize - dist_to_j;
-              let range_to_sub = (sensor.pos.x-left_distance)..=(sensor.pos.x+left_distance);
+        let err = Day15::parse_with(sensor, &Params::from([("column".to_owned(), 3)])).unwrap_err();
+        assert_eq!("unknown parameter \"column\", day 15 takes `row` and `max`", err.to_string());
 
-              ranges.minus(&range_to_sub);
+        Ok(())
+    }
 
-              //println!("ranges : {:?}", ranges.ranges);
-            }
+    #[test]
+    fn all_covered() {
+        let scan = Day15::parse_with("Sensor at x=10, y=10: closest beacon is at x=10, y=30\n", &Day15::params()).unwrap();
 
-            if !ranges.ranges.is_empty() {
-              println!("{:?}", ranges.ranges);
-              assert!(ranges.ranges.len() == 1);
-              assert!(ranges.ranges[0].start() == ranges.ranges[0].end());
-              let x = ranges.ranges[0].start();
-              let y = j;
-              println!("It's here ? : x:{x} y:{y} -> {}", x * 4_000_000 + y);
-              return;
-            }
+        for (name, part2) in Day15::PART2 {
+            let err = part2(&scan).unwrap_err();
+            assert_eq!("no free cell with both coordinates between 0 and 20", err.to_string(), "{name}");
         }
-
     }
 
+    #[test]
+    fn many_free_cells() {
+        let sensors = [Sensor::new(Vec2::new(5000, 5000), Vec2::new(5001, 5000))];
+
+        assert_eq!(Some(0), free_in_row(&sensors, 0, 20));
+    }
 
     #[test]
     fn ops_test() {
 
-      let left = 0..=3;
-      let right = 1..=3;
-      let res = Ranges::range_ops(&left,&right);
+      let mut left = IntervalSet::from(0..=3);
+      left.remove(1..=3);
 
-      assert_eq!(res[0], 0..=0);
+      assert_eq!(Some(0..=0), left.bounds());
     }
 }
@@ -1,129 +1,284 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+//! Day 16: Proboscidea Volcanium.
 
+use std::collections::HashMap;
+
+use eyre::Result;
 use itertools::Itertools;
 
+use nom::{branch::alt, bytes::complete::tag, character::complete::alpha1, combinator::map, sequence::{preceded, tuple}};
 
-#[cfg(test)]
-mod tests {
+use crate::{generate::{Generate, Rng}, log::debug, memo::Memo, parse::{self, Parsed}, search, solution::Solution};
 
-    use super::*;
+pub struct Valves {
+    valves: HashMap<String, isize>,
+    directs: HashMap<String, Vec<String>>,
+}
 
-    #[test]
-    fn adventofcode1() {
-        
-    let mut valves: HashMap<&str, isize> = Default::default();
-    let mut directs : HashMap<&str, Vec<&str>> = Default::default();
+// the most pressure released from a valve, in minutes left, with the valves already open
+type Released<'a> = Memo<(&'a str, isize, i32), isize>;
 
-    include_str!("input.txt").lines().for_each(|s| {
-        let splits = s.splitn(10, " ").collect_vec();
-        let name = splits[1];
-        let flow = splits[4].split_once("=").unwrap().1.split_once(";").unwrap().0.parse::<isize>().unwrap();
-        let targets = splits.last().unwrap().split(", ").collect_vec();
+struct Network<'a> {
+    graph: HashMap<&'a str, HashMap<&'a str, usize>>,
+    indices: HashMap<&'a str, isize>,
+    // the valves with a flow, taking the low bits of the masks
+    flowing: usize,
+    valves: HashMap<&'a str, isize>,
+}
 
-        valves.insert(name, flow);
-        directs.insert(name, targets);
-    });
+impl<'a> Network<'a> {
+    fn new(input: &'a Valves) -> Self {
+        let valves: HashMap<&str, isize> = input.valves.iter().map(|(n, &flow)| (n.as_str(), flow)).collect();
+        let directs: HashMap<&str, Vec<&str>> = input
+            .directs
+            .iter()
+            .map(|(n, targets)| (n.as_str(), targets.iter().map(|t| t.as_str()).collect()))
+            .collect();
 
-    let mut graph : HashMap<&str, HashMap<&str, isize>> = Default::default();
+        debug!("valves : {valves:?}");
+        debug!("directs : {directs:?}");
 
-    println!("valves : {valves:?}");
-    println!("directs : {directs:?}");
+        let no_flow_valves = valves.iter().filter(|(&n, &flow)| flow == 0 && n != "AA").map(|(&n, _)| n).collect_vec();
+        debug!("Stuck valves {no_flow_valves:?}");
 
-    let no_flow_valves = valves.iter().filter(|(&n, &flow)| flow == 0 && n != "AA").map(|(&n, _)| n).collect_vec();
-    println!("Stuck valves {no_flow_valves:?}");
+        // flowing valves take bits 0..n in name order, AA among them when it flows too, or else after
+        // them so part 2 splits on the flowing ones only
+        let flowing = valves.values().filter(|&&flow| flow != 0).count();
+        let indices: HashMap<&str, isize> = valves.iter().filter(|(_, &flow)| flow != 0).map(|(&n, _)| n).sorted().chain(["AA"]).unique().enumerate().map(|(i, name)| (name, i as isize)).collect();
+        debug!("Flowing with index {indices:?}");
 
-    let indices: HashMap<&str, isize> = valves.iter().filter(|(&n, &flow)| flow != 0 || n == "AA").enumerate().map(|(i, (&name, _))| (name, (i + 1) as isize)).collect();
-    println!("Flowing with index {indices:?}");
+        // only the valves worth opening matter, the others are just part of the way between them
+        let mut graph = search::all_pairs(indices.keys().copied(), |&name| directs[name].iter().map(|&next| (next, 1)));
+        graph.values_mut().for_each(|map| map.retain(|n, _| !no_flow_valves.contains(n)));
 
+        Network { graph, indices, flowing, valves }
+    }
 
-    for (&name, _) in valves.iter() {
+    fn dfs(&self, memo: &mut Released<'a>, valve: &'a str, minutes: isize, bitmask: i32) -> isize {
+        memo.get_or_insert_with((valve, minutes, bitmask), |memo| self.most_released(memo, valve, minutes, bitmask))
+    }
 
-        if valves[name] == 0 && name != "AA" {
-            continue;
+    fn most_released(&self, memo: &mut Released<'a>, valve: &'a str, minutes: isize, bitmask: i32) -> isize {
+        let mut max_val = 0;
+        for (&voisin, &dist) in self.graph[valve].iter() {
+            let bit = 1 << self.indices[voisin];
+
+            if bitmask & bit != 0 {
+                continue;
+            }
+
+            let time_remaining = minutes - dist as isize - 1;
+            if time_remaining <= 0 {
+                continue;
+            }
+            let new_bit_mask = bitmask | bit;
+            let new_val = self.dfs(memo, voisin, time_remaining, new_bit_mask) + time_remaining * self.valves[voisin];
+            max_val = max_val.max(new_val);
         }
 
-        graph.insert(name, [(name, 0)].into());
-        let mut queue = VecDeque::new();
-        queue.push_back(name);
+        max_val
+    }
+}
 
-        while let Some(curr) = queue.pop_front() {
-            for &next in directs[curr].iter() {
-                if graph[name].contains_key(next)  {
-                    continue;
-                }
-                let dist = graph[name][curr] + 1;
+fn name(input: &str) -> Parsed<'_, String> {
+    parse::named("a valve name", map(alpha1, str::to_owned))(input)
+}
 
-                graph.get_mut(name).unwrap().insert(next, dist);
-                queue.push_back(next);
+// `Valve <name> has flow rate=<rate>; tunnels lead to valves <name>, <name>`
+fn valve(input: &str) -> Parsed<'_, (String, isize, Vec<String>)> {
+    let tunnels = parse::named(
+        "`; tunnels lead to valves ` or `; tunnel leads to valve `",
+        alt((tag("; tunnels lead to valves "), tag("; tunnel leads to valve "))),
+    );
+
+    tuple((
+        preceded(parse::literal("Valve "), name),
+        preceded(parse::literal(" has flow rate="), parse::integer),
+        preceded(tunnels, parse::comma_list(name)),
+    ))(input)
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Valves;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut valves: HashMap<String, isize> = Default::default();
+        let mut directs : HashMap<String, Vec<String>> = Default::default();
+
+        let lines = parse::each_line(input, |line| parse::line(line, valve))?;
+
+        for ((number, line), (_, _, targets)) in parse::numbered(input).zip(&lines) {
+            if let Some(unknown) = targets.iter().find(|&t| !lines.iter().any(|(name, _, _)| name == t)) {
+                return Err(parse::expected_at(number, line, format!("tunnels to known valves, found {unknown}")));
             }
         }
+        if !lines.iter().any(|(name, _, _)| name == "AA") {
+            return Err(parse::expected("a starting valve AA"));
+        }
+
+        for (name, flow, targets) in lines {
+            valves.insert(name.clone(), flow);
+            directs.insert(name, targets);
+        }
+
+        Ok(Valves { valves, directs })
+    }
+
+    fn part1(valves: &Self::Input) -> isize {
+        Network::new(valves).dfs(&mut Memo::new(), "AA", 30, 0)
     }
 
-    no_flow_valves.iter().for_each(|&n|{ graph.remove(n); });
+    fn part2(valves: &Self::Input) -> isize {
+        let network = Network::new(valves);
+        let mut memo = Memo::new();
 
-    graph.iter_mut().for_each(|(_, map)| {
-        no_flow_valves.iter().for_each(|&n|{ map.remove(n); });
-    });
+        let mut max = 0;
 
-    let cache: HashMap<(&str, isize, i32), isize> = Default::default();
+        let b = (1 << network.flowing) - 1;
+        for i in 0..=b/2 {
+            let res = network.dfs(&mut memo, "AA", 26, i) + network.dfs(&mut memo, "AA", 26, b ^ i );
+            max = max.max(res);
+        }
+
+        debug!("split mask {b:#b}, memo {} with {} results", memo.stats(), memo.len());
 
-    struct Dfs<'a> {
-        cache: HashMap<(&'a str, isize, i32), isize>,
-        graph: HashMap<&'a str, HashMap<&'a str, isize>>,
-        indices: HashMap<&'a str, isize>,
-        valves: HashMap<&'a str, isize>,
-        cache_used: usize,
-        cache_filled: usize,
+        max
     }
+}
 
-    impl Dfs<'static> {
-        fn dfs<'a, 'b>(&'a mut self, valve: &'static str, minutes: isize, bitmask: i32) -> isize {
-            if self.cache.contains_key(&(valve, minutes, bitmask)) {
-                self.cache_used += 1;
-                return self.cache[&(valve, minutes, bitmask)];
+impl Generate for Day16 {
+    // tunnels first span every valve from AA so all of them can be reached, a few more are added
+    // at random; at most 8 valves flow, AA among them at times, part 2 splits them between two in
+    // every way
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut names = vec!["AA".to_owned()];
+        while names.len() < 2 + size {
+            let name = rng.word("ABCDEFGHIJKLMNOPQRSTUVWXYZ", 2);
+            if !names.contains(&name) {
+                names.push(name);
             }
+        }
 
-            let mut max_val = 0;
-            for (&voisin, &dist) in self.graph[valve].clone().iter() {
-                let bit = 1 << self.indices[voisin];
+        let mut tunnels: Vec<Vec<usize>> = vec![vec![]; names.len()];
+        let mut connect = |a: usize, b: usize| {
+            if a != b && !tunnels[a].contains(&b) {
+                tunnels[a].push(b);
+                tunnels[b].push(a);
+            }
+        };
+        for valve in 1..names.len() {
+            connect(valve, rng.below(valve));
+        }
+        for _ in 0..rng.below(size + 1) {
+            connect(rng.below(names.len()), rng.below(names.len()));
+        }
 
-                if bitmask & bit != 0 {
-                    continue;
-                }
-                
-                let time_remaining = minutes - dist - 1;
-                if time_remaining <= 0 {
-                    continue;
+        let mut flowing: Vec<usize> = (0..names.len()).collect();
+        rng.shuffle(&mut flowing);
+        flowing.truncate(1 + rng.below(8.min(names.len())));
+
+        let mut lines: Vec<String> = (0..names.len())
+            .map(|valve| {
+                let flow = if flowing.contains(&valve) { rng.range(1..=25) } else { 0 };
+                let targets: Vec<&str> = tunnels[valve].iter().map(|&t| names[t].as_str()).collect();
+                let tunnels = if targets.len() == 1 { "tunnel leads to valve" } else { "tunnels lead to valves" };
+                format!("Valve {} has flow rate={flow}; {tunnels} {}\n", names[valve], targets.join(", "))
+            })
+            .collect();
+        rng.shuffle(&mut lines);
+
+        lines.concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::input;
+
+    use super::*;
+
+    #[test]
+    fn adventofcode1() -> Result<()> {
+        let valves = Day16::parse(&input::real(16)?)?;
+        assert_eq!(1741, Day16::part1(&valves));
+
+        Ok(())
+    }
+
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let valves = Day16::parse(&input::real(16)?)?;
+        assert_eq!(2316, Day16::part2(&valves));
+
+        Ok(())
+    }
+
+    fn distances<'a>(valves: &'a Valves, from: &'a str) -> HashMap<&'a str, isize> {
+        let mut distances = HashMap::from([(from, 0)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(valve) = queue.pop_front() {
+            for next in &valves.directs[valve] {
+                if !distances.contains_key(next.as_str()) {
+                    distances.insert(next.as_str(), distances[valve] + 1);
+                    queue.push_back(next);
                 }
-                let new_bit_mask = bitmask | bit;
-                let new_val = self.dfs(voisin, time_remaining, new_bit_mask) + time_remaining * self.valves[voisin];
-                max_val = max_val.max(new_val);
             }
-            self.cache.insert((valve, minutes, bitmask), max_val);
-            self.cache_filled += 1;
-
-            max_val
         }
-    }
-    
 
-    let mut dfs = Dfs {
-        cache, graph, indices, valves, cache_filled: 0, cache_used: 0
-    };
+        distances
+    }
 
-    let mut max = 0;
+    // every order of opening flowing valves, with the most released for each set of them opened
+    fn brute_force<'a>(valves: &'a Valves, valve: &'a str, minutes: isize, released: isize, open: &mut Vec<&'a str>, best: &mut HashMap<Vec<&'a str>, isize>) {
+        let key = open.iter().copied().sorted().collect();
+        let most = best.entry(key).or_default();
+        *most = (*most).max(released);
 
-    let b = (1 << (dfs.indices.len() - 1)) - 1;
-    dbg!(b);
-    for i in 0..b/2 {
-        let res = dfs.dfs("AA", 26, i) + dfs.dfs("AA", 26, b ^ i );
-        max = max.max(res);
+        let distances = distances(valves, valve);
+        for (next, &flow) in &valves.valves {
+            let left = minutes - distances[next.as_str()] - 1;
+            if flow == 0 || open.contains(&next.as_str()) || left <= 0 {
+                continue;
+            }
+            open.push(next);
+            brute_force(valves, next, left, released + left * flow, open, best);
+            open.pop();
+        }
     }
 
-    dbg!(dfs.cache_filled);
-    dbg!(dfs.cache_used);
-    dbg!(dfs.cache.len());
-    dbg!(max);
+    #[test]
+    fn flowing_start() -> Result<()> {
+        // AA is opened once, by one of the two in part 2
+        let valves = Day16::parse("Valve AA has flow rate=10; tunnels lead to valves BB, CC\nValve BB has flow rate=20; tunnel leads to valve AA\nValve CC has flow rate=0; tunnel leads to valve AA\n")?;
+        assert_eq!(29 * 10 + 27 * 20, Day16::part1(&valves));
+        assert_eq!(25 * 10 + 24 * 20, Day16::part2(&valves));
+
+        Ok(())
     }
 
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day16>(30, 1..=6, |_, valves, &part1, &part2| {
+            let mut alone = HashMap::new();
+            brute_force(valves, "AA", 30, 0, &mut vec![], &mut alone);
+            assert_eq!(alone.values().copied().max().unwrap(), part1);
+
+            // the elephant opens valves the other one doesn't
+            let mut helped = HashMap::new();
+            brute_force(valves, "AA", 26, 0, &mut vec![], &mut helped);
+            let best = helped
+                .iter()
+                .cartesian_product(&helped)
+                .filter(|((mine, _), (its, _))| mine.iter().all(|valve| !its.contains(valve)))
+                .map(|((_, a), (_, b))| a + b)
+                .max();
+            assert_eq!(best.unwrap(), part2);
+            Ok(())
+        })
+    }
 }
@@ -1,7 +1,19 @@
-use std::{fmt::Debug, iter::Cycle};
+//! Day 17: Pyroclastic Flow.
 
+use eyre::{Result, WrapErr};
 use itertools::Itertools;
-use rayon::vec;
+
+use crate::{
+    cycle,
+    generate::{Generate, Rng},
+    grid::{CellChar, Grid},
+    log::debug,
+    parse,
+    point::Vec2,
+    search,
+    simulation::{Animated, Simulation},
+    solution::{Part, Solution},
+};
 
 enum Form {
     HorizonalLine,
@@ -12,14 +24,16 @@ enum Form {
 }
 
 impl Form {
-    fn get_parts(&self) -> Vec<(isize, isize)> {
-        match self {
-            Form::HorizonalLine => vec![(0, 0), (1, 0), (2, 0), (3, 0)],
-            Form::Plus => vec![(0, 1), (1, 0), (1, 1), (2, 1), (1, 2)],
-            Form::ReverseL => vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
-            Form::VerticalLine => vec![(0, 0), (0, 1), (0, 2), (0, 3)],
-            Form::Square => vec![(0, 0), (1, 0), (0, 1), (1, 1)],
-        }
+    fn get_parts(&self) -> Vec<Vec2> {
+        let parts: &[(isize, isize)] = match self {
+            Form::HorizonalLine => &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            Form::Plus => &[(0, 1), (1, 0), (1, 1), (2, 1), (1, 2)],
+            Form::ReverseL => &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            Form::VerticalLine => &[(0, 0), (0, 1), (0, 2), (0, 3)],
+            Form::Square => &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        };
+
+        parts.iter().map(|&part| part.into()).collect()
     }
 
     fn get_vertical_heigh(&self) -> isize {
@@ -32,16 +46,6 @@ impl Form {
         }
     }
 
-    fn get_name(&self) -> &str {
-        match self {
-            Form::HorizonalLine => "HorizonalLine",
-            Form::Plus => "Plus",
-            Form::ReverseL => "ReverseL",
-            Form::VerticalLine => "VerticalLine",
-            Form::Square => "Square",
-        }
-    }
-
     fn iter() -> [Form; 5] {
         [
             Form::HorizonalLine,
@@ -60,102 +64,100 @@ enum Block {
     Rock,
 }
 
-impl Debug for Block {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl CellChar for Block {
+    fn char(&self) -> char {
         match self {
-            Self::Empty => write!(f, "."),
-            Self::Wall => write!(f, "-"),
-            Self::Rock => write!(f, "#"),
+            Self::Empty => '.',
+            Self::Wall => '-',
+            Self::Rock => '#',
         }
     }
 }
 
+const WIDTH: usize = 7;
+const SURFACE_DEPTH: usize = 64;
+
 struct Terrain {
-    tower: Vec<[Block; 7]>,
-    current_block: Option<Vec<(isize, isize)>>,
+    // row 0 is the floor, the tower grows with the row index
+    tower: Grid<Block>,
+    current_block: Option<Vec<Vec2>>,
 }
 
-impl Debug for Terrain {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let size = self.tower.len();
-        for (y, blocks) in self.tower.iter().enumerate().rev() {
-            if y < size - 15 {
+impl Terrain {
+    // the top `rows` rows, the falling rock drawn as `@`
+    fn render(&self, rows: usize) -> String {
+        let size = self.tower.height();
+        let mut out = String::new();
+        for (y, blocks) in self.tower.rows().enumerate().rev() {
+            if y < size.saturating_sub(rows) {
                 continue;
             }
-            write!(f, "|")?;
+            out.push('|');
             for (x, block) in blocks.iter().enumerate() {
-                if let Some(vals) = &self.current_block {
-                    if vals.contains(&(x as isize, y as isize)) {
-                        write!(f, "@")?;
-                    } else {
-                        block.fmt(f)?;
-                    }
-                } else {
-                    block.fmt(f)?;
-                }
+                let falling = self.current_block.as_ref().is_some_and(|vals| vals.contains(&Vec2::new(x as isize, y as isize)));
+                out.push(if falling { '@' } else { block.char() });
             }
-            writeln!(f, "|")?;
+            out.push_str("|\n");
         }
 
-        Ok(())
+        out
     }
-}
 
-impl Terrain {
     fn new() -> Self {
-        let tower = vec![[Block::Wall; 7]];
+        let mut tower = Grid::new(WIDTH, 0, Block::Empty);
+        tower.push_row([Block::Wall; WIDTH]);
         Self {
             tower,
             current_block: None,
         }
     }
 
-    fn starting_pos(&mut self, form: &Form) -> (isize, isize) {
-        if self.current_block != None {
+    fn starting_pos(&mut self, form: &Form) -> Vec2 {
+        if self.current_block.is_some() {
             self.current_block = None;
         }
 
         let x = 2;
         let (index, _) = self
             .tower
-            .iter()
+            .rows()
             .enumerate()
             .rev()
-            .find(|(_, &etage)| etage.contains(&Block::Rock) || etage.contains(&Block::Wall))
+            .find(|(_, etage)| etage.contains(&Block::Rock) || etage.contains(&Block::Wall))
             .unwrap();
         let y = index as isize + 4;
 
         let form_vertical_size = form.get_vertical_heigh();
-        while (self.tower.len() as isize) < (y + form_vertical_size) {
-            self.tower.push([Block::Empty; 7]);
+        while (self.tower.height() as isize) < (y + form_vertical_size) {
+            self.tower.push_row([Block::Empty; WIDTH]);
         }
 
         let current_block = form
             .get_parts()
             .into_iter()
-            .map(|(dx, dy)| (x + dx, y + dy))
+            .map(|part| Vec2::new(x, y) + part)
             .collect_vec();
 
         // println!("current_block: {current_block:?}");
 
         self.current_block = Some(current_block);
 
-        (x, y)
+        Vec2::new(x, y)
     }
 
-    fn moving(&mut self, dir: &Direction) -> Option<()> {
+    // pushes the rock then lets it fall, true once it came to rest
+    fn moving(&mut self, dir: &Direction) -> bool {
         self.apply(dir);
-        self.apply(&Direction::Down)
+        self.apply(&Direction::Down).is_none()
     }
 
     fn apply(&mut self, dir: &Direction) -> Option<()> {
         let mut current_blocks = self.current_block.take().unwrap();
         let old_pos = current_blocks.clone();
-        let (dx, dy) = dir.transform();
+        let delta = dir.transform();
 
-        for (x, y) in current_blocks.iter_mut() {
-            *x += dx;
-            *y += dy;
+        for pos in current_blocks.iter_mut() {
+            *pos += delta;
         }
         self.current_block = Some(current_blocks);
 
@@ -165,8 +167,8 @@ impl Terrain {
                     self.current_block = Some(old_pos);
                 }
                 Direction::Down => {
-                    for (x, y) in old_pos {
-                        self.tower[y as usize][x as usize] = Block::Rock;
+                    for pos in old_pos {
+                        self.tower[pos] = Block::Rock;
                         self.current_block = None;
                     }
                     return None;
@@ -178,197 +180,226 @@ impl Terrain {
     }
 
     fn block_is_ok(&mut self) -> bool {
-        if let Some(vec) = &self.current_block {
-            for (x, y) in vec {
-                if x >= &7 || x < &0 {
-                    return false;
-                }
-
-                if y < &0 {
-                    return false;
-                }
-
-                if self.tower[*y as usize][*x as usize] != Block::Empty {
-                    return false;
-                }
-            }
-        }
-
-        return true;
-    }
-
-    fn dist_to_high(&self) -> [isize; 7] {
-        let mut dist = [isize::MAX; 7];
-
-        let tower_size = self
-            .tower
+        // walls, the floor and settled rocks all stop the block
+        self.current_block
             .iter()
-            .filter(|&s| s.contains(&Block::Rock))
-            .collect_vec()
-            .len();
-
-        for i in (1..=tower_size).rev() {
-            for j in 0..7 {
-                if dist[j] != isize::MAX {
-                    continue;
-                }
-
-                if self.tower[i][j] != Block::Empty {
-                    dist[j] = (tower_size - i) as isize;
-                }
-            }
-
-            if dist.iter().all(|s| s != &isize::MAX) {
-                return dist;
-            }
-        }
+            .flatten()
+            .all(|&pos| self.tower.get(pos) == Some(&Block::Empty))
+    }
 
-        dist.iter_mut()
-            .filter(|s| s == &&isize::MAX)
-            .for_each(|s| *s = tower_size as isize);
+    fn rock_height(&self) -> usize {
+        self.tower.rows().filter(|row| row.contains(&Block::Rock)).count()
+    }
 
-        dist
+    // the empty cells a falling rock could still get to, from the top of the tower down to
+    // `SURFACE_DEPTH` rows; deeper ones don't change how high the tower grows, and keeping them
+    // would stop an untouched column's surface from ever repeating
+    fn surface(&self) -> Vec<(isize, isize)> {
+        let top = self.rock_height() as isize;
+        let open = |pos: &Vec2| {
+            (0..WIDTH as isize).contains(&pos.x)
+                && top - pos.y < SURFACE_DEPTH as isize
+                && (pos.y > top || self.tower.get(*pos) == Some(&Block::Empty))
+        };
+
+        let reached = search::bfs((0..WIDTH as isize).map(|x| Vec2::new(x, top + 1)), |&pos| {
+            [pos + Vec2::LEFT, pos + Vec2::RIGHT, pos + Vec2::new(0, -1)].into_iter().filter(open)
+        });
+
+        reached.distances().keys().map(|pos| (pos.x, top - pos.y)).sorted().collect()
     }
 }
 
 #[derive(Debug, Clone)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
     Down,
 }
 
 impl Direction {
-    fn transform(&self) -> (isize, isize) {
+    // the tower grows with the row index, so falling goes towards row 0
+    fn transform(&self) -> Vec2 {
         match self {
-            Direction::Left => (-1, 0),
-            Direction::Right => (1, 0),
-            Direction::Down => (0, -1),
+            Direction::Left => Vec2::LEFT,
+            Direction::Right => Vec2::RIGHT,
+            Direction::Down => Vec2::new(0, -1),
         }
     }
 }
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Direction {
+    type Error = eyre::Report;
+
+    fn try_from(value: char) -> Result<Self> {
         match value {
-            '>' => Direction::Right,
-            '<' => Direction::Left,
-            _ => unreachable!(),
+            '>' => Ok(Direction::Right),
+            '<' => Ok(Direction::Left),
+            _ => Err(parse::expected(format!("a jet `<` or `>`, found {value:?}"))),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{collections::HashMap, process::exit};
+// the tower between two rocks, with the next jet to blow
+struct Fall {
+    terrain: Terrain,
+    jets: Vec<Direction>,
+    jet: usize,
+    rocks: usize,
+    target: u128,
+}
 
-    use itertools::Itertools;
+impl Fall {
+    fn new(jets: Vec<Direction>, target: u128) -> Self {
+        Fall { terrain: Terrain::new(), jets, jet: 0, rocks: 0, target }
+    }
 
-    use super::*;
+    fn drop_rock(&mut self) {
+        self.step();
+        while self.terrain.current_block.is_some() {
+            self.step();
+        }
+    }
+}
 
-    #[test]
-    fn adventofcode1() {
-        let dirs: Vec<Direction> = include_str!("input.txt")
-            .lines()
-            .next()
-            .unwrap()
-            .chars()
-            .map(|c| c.into())
-            .collect_vec();
-        let forms = Form::iter();
-        //println!("{directions:?}");
+impl Simulation for Fall {
+    // a new rock appears, or the falling one is pushed by a jet then falls by one row
+    fn step(&mut self) {
+        if self.is_done() {
+            return;
+        }
+        if self.terrain.current_block.is_none() {
+            let forms = Form::iter();
+            self.terrain.starting_pos(&forms[self.rocks % forms.len()]);
+            return;
+        }
 
-        let mut terrain = Terrain::new();
+        let dir = &self.jets[self.jet];
+        self.jet = (self.jet + 1) % self.jets.len();
+        if self.terrain.moving(dir) {
+            self.rocks += 1;
+        }
+    }
 
-        let mut dir_index = None;
-        let mut dir = &dirs[0];
+    fn is_done(&self) -> bool {
+        self.rocks as u128 >= self.target
+    }
 
-        let mut hashmap: HashMap<(usize, usize, [isize; 7]), (u128, usize)> = Default::default();
-        let mut short_cut_found = false;
+    fn render(&self) -> String {
+        self.terrain.render(30)
+    }
 
-        let max_nbr_block = 1000000000000;
+    fn status(&self) -> String {
+        format!("{} rocks, {} rows high", self.rocks, self.terrain.rock_height())
+    }
+}
 
-        let mut skipped_height = 0;
+fn tower_height(jets: &[Direction], max_nbr_block: u128) -> u128 {
+    let fall = Fall::new(jets.to_vec(), max_nbr_block);
+
+    // the same rock and jet coming up over the same surface repeat what happened last time
+    let history = cycle::detect(
+        fall,
+        usize::try_from(max_nbr_block).unwrap_or(usize::MAX),
+        Fall::drop_rock,
+        |fall| (fall.rocks % Form::iter().len(), fall.jet, fall.terrain.surface()),
+        |fall| fall.terrain.rock_height() as u128,
+    );
+    if let Some(cycle) = history.cycle() {
+        debug!("{cycle}, {} rows high", history.metrics()[cycle.start + cycle.length] - history.metrics()[cycle.start]);
+    }
 
-        let mut i = 0u128;
-        while i < max_nbr_block {
-            let form_index = (i % 5) as usize;
-            let form = &forms[form_index];
-            // print!(
-            //     "Placing {} ({form_index}; {i}) dir index : {} {dir_index:?}",
-            //     form.get_name(),
-            //     dir_index.unwrap_or(0) % dirs.len()
-            // );
-            terrain.starting_pos(form);
+    history.extrapolate(max_nbr_block).expect("the tower either repeats or is built up to the end")
+}
 
-            if i == 1989 {
-                println!("{terrain:?}");
-            }
+pub struct Day17;
 
-            loop {
-                let dist_to_highest = terrain.dist_to_high();
-                dir_index = Some(dir_index.take().map(|v| v + 1).unwrap_or(0));
-
-                let key = (form_index, dir_index.unwrap() % dirs.len(), dist_to_highest);
-                let mut current_height = terrain
-                    .tower
-                    .iter()
-                    .filter(|l| l.contains(&Block::Rock))
-                    .collect_vec()
-                    .len();
-
-                dir = &dirs[dir_index.unwrap() % dirs.len()];
-                let res = terrain.moving(dir);
-
-                if res.is_none() {
-                    //println!(" ---- Placed after {dir_index:?}");
-
-                    if !short_cut_found && hashmap.contains_key(&key) {
-                      short_cut_found = true;
-                      let (number_of_block, height) = hashmap.get(&key).unwrap();
-                      let cycle_size = dirs.len();
-                      let height_effect = current_height - height;
-                      let block_effect = i - number_of_block;
-                      //println!("");
-                      //println!("Found a loop : {cycle_size}, {height_effect} higher with {block_effect} blocks");
-                      //println!("{key:?}");
-  
-                      let nbr_loop = (max_nbr_block - i) / block_effect;
-  
-                      i += nbr_loop * block_effect;
-                      skipped_height += nbr_loop * height_effect as u128;
-                      //dir_index = Some(dir_index.take().map(|v| v+5).unwrap_or(0));
-  
-                      //i -= 1;
-                      //println!("After using the loop {nbr_loop} times : {i} blocks used - {skipped_height} skipped_height");
-                      break;
-                  } else if !short_cut_found {
-                      hashmap.insert(
-                          (form_index, dir_index.unwrap() % dirs.len(), dist_to_highest),
-                          (i, current_height),
-                      );
-                  }
-
-                    break;
-                }
-            }
+impl Solution for Day17 {
+    type Input = Vec<Direction>;
+    type Answer1 = u128;
+    type Answer2 = u128;
 
-            if i == 1989 {
-                //println!("{terrain:?}");
-            }
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut lines = parse::numbered(input);
+        let (_, jets) = lines.next().unwrap_or_default();
+        if let Some((number, line)) = lines.next() {
+            return Err(parse::expected_at(number, line, "the jet pattern on a single line"));
+        }
 
-            i += 1;
+        let directions = jets
+            .chars()
+            .map(Direction::try_from)
+            .collect::<Result<Vec<_>>>()
+            .wrap_err_with(|| parse::at_line(1, jets))?;
+        if directions.is_empty() {
+            return Err(parse::expected_at(1, jets, "a jet pattern of `<` and `>`"));
         }
 
-        println!(
-            "res: {}",
-            terrain
-                .tower
-                .iter()
-                .filter(|l| l.contains(&Block::Rock))
-                .collect_vec()
-                .len() as u128
-                + skipped_height
-        );
+        Ok(directions)
+    }
+
+    fn part1(dirs: &Self::Input) -> u128 {
+        tower_height(dirs, 2022)
+    }
+
+    fn part2(dirs: &Self::Input) -> u128 {
+        tower_height(dirs, 1000000000000)
+    }
+}
+
+impl Animated for Day17 {
+    fn simulation(dirs: Self::Input, part: Part) -> Box<dyn Simulation> {
+        let rocks = match part {
+            Part::One => 2022,
+            Part::Two => 1000000000000,
+        };
+
+        Box::new(Fall::new(dirs, rocks))
+    }
+}
+
+impl Generate for Day17 {
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let len = 1 + rng.below(40 * size);
+        let jets = rng.word("<>", len);
+
+        format!("{jets}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input;
+
+    use super::*;
+
+    #[test]
+    fn adventofcode1() -> Result<()> {
+        let dirs = Day17::parse(&input::real(17)?)?;
+        assert_eq!(3106, Day17::part1(&dirs));
+
+        Ok(())
+    }
+
+    #[test]
+    fn adventofcode2() -> Result<()> {
+        let dirs = Day17::parse(&input::real(17)?)?;
+        assert_eq!(1537175792495, Day17::part2(&dirs));
+
+        Ok(())
+    }
+
+    #[test]
+    fn generated_inputs() -> Result<()> {
+        crate::generate::check::<Day17>(30, 1..=10, |_, dirs, &height, _| {
+            // every rock dropped, no cycle to skip over
+            let mut fall = Fall::new(dirs.clone(), 2022);
+            while !fall.is_done() {
+                fall.drop_rock();
+            }
+
+            assert_eq!(fall.terrain.rock_height() as u128, height);
+            Ok(())
+        })
     }
 }
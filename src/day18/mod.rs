@@ -1,119 +1,148 @@
-use std::{collections::HashSet, fmt::Debug};
+//! Day 18: Boiling Boulders.
 
+use std::{collections::HashSet, str::FromStr};
+
+use eyre::Result;
 use itertools::Itertools;
 
-#[derive(Hash, PartialEq, Eq, Clone)]
-struct Coord {
-  vals: (i32, i32, i32)
+use crate::{
+  crosscheck::{Implementation, Variants},
+  generate::{Generate, Rng},
+  log::{debug, trace},
+  parse,
+  point::Vec3,
+  search::{self, Paths},
+  solution::Solution,
+};
+
+fn free_faces(lava_set: &HashSet<Vec3>) -> Vec<Vec3> {
+  let all_possibles: Vec<_> = lava_set.iter().flat_map(|val| val.neighbours6()).collect();
+  debug!("Max Len : {:?}", all_possibles.iter().unique().count());
+
+  all_possibles.into_iter().filter(|val| !lava_set.contains(val)).collect_vec()
 }
 
-impl From<(i32, i32, i32)> for Coord {
-    fn from(vals: (i32, i32, i32)) -> Self {
-        Coord { vals }
-    }
+pub struct Day18;
+
+impl Solution for Day18 {
+  type Input = HashSet<Vec3>;
+  type Answer1 = usize;
+  type Answer2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    Ok(parse::each_line(input, Vec3::from_str)?.into_iter().collect())
+  }
+
+  fn part1(lava_set: &Self::Input) -> usize {
+    free_faces(lava_set).len()
+  }
+
+  fn part2(lava_set: &Self::Input) -> usize {
+    trace!("{lava_set:?}");
+
+    let filtered = free_faces(lava_set);
+    debug!("Filtered size {}", filtered.len());
+
+    let outside = outside_air(lava_set);
+
+    filtered.iter().filter(|&val| outside.contains(val)).count()
+  }
 }
 
-impl Debug for Coord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.vals.fmt(f)
-    }
+// air the steam reaches from outside, anything else is trapped
+fn outside_air(lava_set: &HashSet<Vec3>) -> Paths<Vec3> {
+  // a box one cube larger than the droplet on every side, whose outer layer is all air
+  let (min_x, max_x) = lava_set.iter().map(|v| v.x).minmax().into_option().unwrap();
+  let (min_y, max_y) = lava_set.iter().map(|v| v.y).minmax().into_option().unwrap();
+  let (min_z, max_z) = lava_set.iter().map(|v| v.z).minmax().into_option().unwrap();
+  let min = Vec3::new(min_x - 1, min_y - 1, min_z - 1);
+  let max = Vec3::new(max_x + 1, max_y + 1, max_z + 1);
+  let inside = move |v: &Vec3| {
+    (min.x..=max.x).contains(&v.x) && (min.y..=max.y).contains(&v.y) && (min.z..=max.z).contains(&v.z)
+  };
+
+  let outside = search::bfs([min], |air| {
+    air.neighbours6().into_iter().filter(|next| inside(next) && !lava_set.contains(next))
+  });
+  trace!("outside air : {}", outside.distances().len());
+
+  outside
 }
 
-impl From<&str> for Coord {
-    fn from(value: &str) -> Self {
-       let vals: (i32, i32, i32) = value.split(",").map(|i| i.parse::<i32>().unwrap()).collect_tuple().unwrap();
+// six faces per cube, less the two hidden by each pair of touching cubes
+fn surface_by_pairs(lava_set: &HashSet<Vec3>) -> usize {
+  let touching = lava_set.iter().flat_map(|cube| cube.neighbours6()).filter(|next| lava_set.contains(next)).count();
 
-       Coord{
-        vals
-       }
-    }
+  6 * lava_set.len() - touching
 }
 
-impl Coord {
-  fn get_voisin(&self) -> [Coord; 6] {
-    let vals = self.vals;
-    [
-      (vals.0 + 1, vals.1, vals.2).into(), (vals.0 - 1, vals.1, vals.2).into(),
-      (vals.0, vals.1 + 1, vals.2).into(), (vals.0, vals.1 - 1, vals.2).into(),
-      (vals.0, vals.1, vals.2 + 1).into(), (vals.0, vals.1, vals.2 - 1).into()
-    ]
-  }
+// the faces the steam touches, counted from the air side
+fn surface_from_steam(lava_set: &HashSet<Vec3>) -> usize {
+  outside_air(lava_set)
+    .distances()
+    .keys()
+    .map(|air| air.neighbours6().into_iter().filter(|next| lava_set.contains(next)).count())
+    .sum()
+}
+
+impl Variants for Day18 {
+  const PART1: &'static [Implementation<HashSet<Vec3>, usize>] = &[("free_faces", Day18::part1), ("pairs", surface_by_pairs)];
+  const PART2: &'static [Implementation<HashSet<Vec3>, usize>] = &[("free_faces", Day18::part2), ("steam", surface_from_steam)];
 }
 
-enum Type {
-  Lava,
-  Outside,
-  Inside
+impl Generate for Day18 {
+  // cubes stuck together by a random walk, leaving pockets of air here and there
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    let side = 2 + size as isize;
+    let mut cube = Vec3::new(side / 2, side / 2, side / 2);
+    let mut lava = vec![cube];
+    for _ in 0..8 * size {
+      let next = cube + *rng.pick(&Vec3::NEIGHBOURS6);
+      if [next.x, next.y, next.z].iter().all(|c| (0..side).contains(c)) {
+        cube = next;
+      }
+      if !lava.contains(&cube) {
+        lava.push(cube);
+      }
+    }
+    rng.shuffle(&mut lava);
+
+    lava.iter().map(|cube| format!("{},{},{}\n", cube.x, cube.y, cube.z)).collect()
+  }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::VecDeque;
+    use crate::input;
 
     use super::*;
 
 
   #[test]
-  fn adventofcode1() {
-    let lava_set: HashSet<Coord> = include_str!("input.txt").lines().map(|s| s.into()).collect();
-    println!("{lava_set:?}");
-
-    let all_possibles: Vec<_> = lava_set.iter().flat_map(|val| val.get_voisin()).collect();
-    println!("Max Len : {:?}", all_possibles.iter().unique().collect_vec().len());
-
-    let filtered = all_possibles.iter().filter(|&val| !lava_set.contains(val)).collect_vec();
-    println!("Filtered size {}", filtered.len());
-    
-    let (coord_min_x, coord_max_x) = lava_set.iter().minmax_by_key(|v| v.vals.0).into_option().map(|(min, max)| (min.vals.0, max.vals.0)).unwrap();
-    let (coord_min_y, coord_max_y) = lava_set.iter().minmax_by_key(|v| v.vals.1).into_option().map(|(min, max)| (min.vals.1, max.vals.1)).unwrap();
-    let (coord_min_z, coord_max_z) = lava_set.iter().minmax_by_key(|v| v.vals.2).into_option().map(|(min, max)| (min.vals.2, max.vals.2)).unwrap();
-
-    let mut air_trapped = HashSet::new();
-
-    for air in filtered {
-      //println!("Testing air : {air:?}");
-      let mut set = HashSet::new();
-      let mut vec: VecDeque<Coord> = VecDeque::new();
-      vec.push_back(air.clone());
-
-      let mut res = true;
-
-      while let Some(air) = vec.pop_front() {
-        if air.vals.0 < coord_min_x || air.vals.0 > coord_max_x ||
-          air.vals.1 < coord_min_y || air.vals.1 > coord_max_y ||
-          air.vals.2 < coord_min_z || air.vals.2 > coord_max_z {
-            vec = VecDeque::new();
-            res = false;
-            continue;
-          }
-        
-        if set.contains(&air) {
-          continue;
-        }
-
-        for next in air.get_voisin() {
-          if !lava_set.contains(&next) {
-            vec.push_back(next);
-          } 
-        }
-
-        set.insert(air);
-      }
-
-      if res {
-        //println!("Is trapped");
-        air_trapped.insert(air);
-      }else {
-        //println!("Is outside");
-      }
-
-    }
+  fn adventofcode1() -> Result<()> {
+    let lava_set = Day18::parse(&input::real(18)?)?;
+    assert_eq!(4390, Day18::part1(&lava_set));
 
-    println!("air_trapped : {air_trapped:?}");
+    Ok(())
+  }
 
+  #[test]
+  fn adventofcode2() -> Result<()> {
+    let lava_set = Day18::parse(&input::real(18)?)?;
+    assert_eq!(2534, Day18::part2(&lava_set));
 
-    let res = all_possibles.iter().filter(|&val| !lava_set.contains(val) && !air_trapped.contains(val)).collect_vec();
-    println!("All res : {}", res.len());
+    Ok(())
+  }
 
+  #[test]
+  fn generated_inputs() -> Result<()> {
+    crate::generate::check::<Day18>(30, 1..=8, |_, lava_set, &surface, &exterior| {
+      // each pair of touching cubes hides a face of both
+      let touching = lava_set.iter().flat_map(|cube| cube.neighbours6()).filter(|next| lava_set.contains(next)).count();
+
+      assert_eq!(6 * lava_set.len() - touching, surface);
+      assert!(exterior <= surface);
+      Ok(())
+    })
   }
-}
\ No newline at end of file
+}
@@ -1,6 +1,12 @@
-use std::{ops::{Add, Sub, AddAssign, SubAssign}, iter::Sum};
+//! Day 19: Not Enough Minerals.
 
-#[derive(Debug)]
+use std::{ops::{Add, Sub, AddAssign, SubAssign, Mul}, iter::Sum};
+
+use eyre::Result;
+
+use crate::{generate::{Generate, Rng}, log::debug, memo::Memo, parse, solution::Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Cost {
   ore: i32,
   clay: i32,
@@ -52,6 +58,19 @@ impl Add for Cost {
     }
 }
 
+impl Mul<i32> for Cost {
+    type Output = Cost;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Cost {
+          ore: self.ore * rhs,
+          clay: self.clay * rhs,
+          obsidian: self.obsidian * rhs,
+          geode: self.geode * rhs
+        }
+    }
+}
+
 impl Sum for Cost {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
         iter.fold(Cost::new(), |a ,b| {
@@ -107,11 +126,38 @@ impl Cost {
       geode: input
     }
   }
+
+  // minutes to wait before `stock` covers this cost, `None` if nothing produces what is missing
+  fn wait_for(&self, stock: &Cost, robots: &Cost) -> Option<i32> {
+    [
+      (self.ore, stock.ore, robots.ore),
+      (self.clay, stock.clay, robots.clay),
+      (self.obsidian, stock.obsidian, robots.obsidian),
+    ]
+    .into_iter()
+    .map(|(cost, have, rate)| {
+      if cost <= have {
+        Some(0)
+      } else if rate == 0 {
+        None
+      } else {
+        Some((cost - have + rate - 1) / rate)
+      }
+    })
+    .try_fold(0, |acc, wait| Some(acc.max(wait?)))
+  }
 }
 
+// states already searched from, as minutes left, robots and stock
+type Explored = Memo<(i32, Cost, Cost), ()>;
+
+const EXPLORED_CAPACITY: usize = 1 << 16;
+// closer to the end, searching a state again costs less than remembering it
+const EXPLORED_MIN_TIME: i32 = 14;
+
 #[derive(Debug)]
-struct Blueprint {
-    number: i8,
+pub struct Blueprint {
+    number: i32,
     ore_robot_cost: Cost,
     clay_robot_cost: Cost,
     obsidian_robot_cost: Cost,
@@ -119,25 +165,87 @@ struct Blueprint {
 }
 
 impl Blueprint {
-    fn new(str: &str) -> Blueprint {
+    fn new(str: &str) -> Result<Blueprint> {
         // Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 5 clay. Each geode robot costs 3 ore and 7 obsidian.
+        let template = "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+            Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.";
+        let [number, ore_robot_cost, clay_robot_cost, obsidian_robot_cost_ore, obsidian_robot_cost_clay, geode_robot_cost_ore, geode_robot_cost_obsidian] =
+            parse::line(str, parse::template(template, parse::integer))?;
+
+        Ok(Blueprint {
+            number,
+            ore_robot_cost: Cost::ore(ore_robot_cost),
+            clay_robot_cost: Cost::ore(clay_robot_cost),
+            obsidian_robot_cost: Cost::ore(obsidian_robot_cost_ore) + Cost::clay(obsidian_robot_cost_clay),
+            geode_robot_cost: Cost::ore(geode_robot_cost_ore) + Cost::obsidian(geode_robot_cost_obsidian),
+        })
+    }
+
+    fn cost(&self, robot: &Robots) -> &Cost {
+        match robot {
+            Robots::Ore => &self.ore_robot_cost,
+            Robots::Clay => &self.clay_robot_cost,
+            Robots::Obsidian => &self.obsidian_robot_cost,
+            Robots::Geode => &self.geode_robot_cost,
+        }
+    }
+
+    fn max_geodes(&self, minutes: i32, explored: &mut Explored) -> i32 {
+        // no point in producing more of a resource per minute than we can spend
+        let max_spend = Cost {
+            ore: [&self.ore_robot_cost, &self.clay_robot_cost, &self.obsidian_robot_cost, &self.geode_robot_cost]
+                .iter()
+                .map(|c| c.ore)
+                .max()
+                .unwrap(),
+            clay: self.obsidian_robot_cost.clay,
+            obsidian: self.geode_robot_cost.obsidian,
+            geode: i32::MAX,
+        };
+
+        let mut best = 0;
+        explored.clear();
+        self.search(explored, minutes, Robots::Ore.produce(), Cost::new(), &max_spend, &mut best);
+        debug!("blueprint {}: {} geodes, memo {}", self.number, best, explored.stats());
+        best
+    }
+
+    fn search(&self, explored: &mut Explored, time_left: i32, robots: Cost, stock: Cost, max_spend: &Cost, best: &mut i32) {
+        if time_left < EXPLORED_MIN_TIME {
+            return self.explore(explored, time_left, robots, stock, max_spend, best);
+        }
+
+        // `best` only grows, so a state explored before has nothing better to offer
+        explored.get_or_insert_with((time_left, robots, stock), |explored| {
+            self.explore(explored, time_left, robots, stock, max_spend, best)
+        })
+    }
+
+    fn explore(&self, explored: &mut Explored, time_left: i32, robots: Cost, stock: Cost, max_spend: &Cost, best: &mut i32) {
+        let idle = stock.geode + robots.geode * time_left;
+        *best = (*best).max(idle);
 
-        let (_, str) = str.split_once("Blueprint ").unwrap();
-        let (number, str) = str.split_once(": Each ore robot costs ").unwrap();
-        let (ore_robot_cost, str) = str.split_once(" ore. Each clay robot costs ").unwrap();
-        let (clay_robot_cost, str) =
-            str.split_once(" ore. Each obsidian robot costs ").unwrap();
-        let (obsidian_robot_cost_ore, str) = str.split_once(" ore and ").unwrap();
-        let (obsidian_robot_cost_clay, str) = str.split_once(" clay. Each geode robot costs ").unwrap();
-        let (geode_robot_cost_ore, str) = str.split_once(" ore and ").unwrap();
-        let (geode_robot_cost_obsidian, str) = str.split_once(" obsidian.").unwrap();
-
-        Blueprint {
-            number: number.parse::<i8>().unwrap(),
-            ore_robot_cost: Cost::ore(ore_robot_cost.parse::<i32>().unwrap()),
-            clay_robot_cost: Cost::ore(clay_robot_cost.parse::<i32>().unwrap()),
-            obsidian_robot_cost: Cost::ore(obsidian_robot_cost_ore.parse::<i32>().unwrap()) + Cost::clay(obsidian_robot_cost_clay.parse::<i32>().unwrap()),
-            geode_robot_cost: Cost::ore(geode_robot_cost_ore.parse::<i32>().unwrap()) + Cost::obsidian(geode_robot_cost_obsidian.parse::<i32>().unwrap()),
+        // even building a geode robot every remaining minute can't beat it
+        if idle + time_left * (time_left - 1) / 2 <= *best {
+            return;
+        }
+
+        for robot in [Robots::Geode, Robots::Obsidian, Robots::Clay, Robots::Ore] {
+            if robot.count(&robots) >= robot.count(max_spend) {
+                continue;
+            }
+
+            let cost = self.cost(&robot);
+            let Some(wait) = cost.wait_for(&stock, &robots) else {
+                continue;
+            };
+            if wait + 1 >= time_left {
+                continue;
+            }
+
+            let stock = stock + robots * (wait + 1) - *cost;
+            let robots = robots + robot.produce();
+            self.search(explored, time_left - wait - 1, robots, stock, max_spend, best);
         }
     }
 }
@@ -158,33 +266,116 @@ impl Robots {
         Robots::Geode => Cost::geode(1),
     }
   }
+
+  fn count(&self, cost: &Cost) -> i32 {
+    match self {
+        Robots::Ore => cost.ore,
+        Robots::Clay => cost.clay,
+        Robots::Obsidian => cost.obsidian,
+        Robots::Geode => cost.geode,
+    }
+  }
 }
 
+pub struct Day19;
+
+impl Solution for Day19 {
+  type Input = Vec<Blueprint>;
+  type Answer1 = i32;
+  type Answer2 = i32;
+
+  fn parse(input: &str) -> Result<Self::Input> {
+    parse::each_line(input, Blueprint::new)
+  }
+
+  fn part1(blueprints: &Self::Input) -> i32 {
+    let mut explored = Memo::bounded(EXPLORED_CAPACITY);
+    blueprints
+      .iter()
+      .map(|bp| bp.number * bp.max_geodes(24, &mut explored))
+      .sum()
+  }
+
+  fn part2(blueprints: &Self::Input) -> i32 {
+    let mut explored = Memo::bounded(EXPLORED_CAPACITY);
+    blueprints
+      .iter()
+      .take(3)
+      .map(|bp| bp.max_geodes(32, &mut explored))
+      .product()
+  }
+}
+
+impl Generate for Day19 {
+  // costs in the same ranges as the puzzle inputs
+  fn generate(rng: &mut Rng, size: usize) -> String {
+    (1..=size)
+      .map(|number| {
+        let [ore, clay, obsidian_ore, geode_ore] = [(); 4].map(|_| rng.range(2..=4));
+        let (obsidian_clay, geode_obsidian) = (rng.range(5..=20), rng.range(7..=20));
+
+        format!(
+          "Blueprint {number}: Each ore robot costs {ore} ore. Each clay robot costs {clay} ore. \
+            Each obsidian robot costs {obsidian_ore} ore and {obsidian_clay} clay. \
+            Each geode robot costs {geode_ore} ore and {geode_obsidian} obsidian.\n"
+        )
+      })
+      .collect()
+  }
+}
 
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
+    use crate::input;
 
     use super::*;
 
     #[test]
-    fn adventofcode1() {
-      let bps = include_str!("input.txt").lines().map(|s| Blueprint::new(s)).collect_vec();
+    fn adventofcode1() -> Result<()> {
+      let blueprints = Day19::parse(&input::real(19)?)?;
+      assert_eq!(33, Day19::part1(&blueprints));
 
-      for blueprint in bps {
-
-        let mut robots = vec![Robots::Ore];
-        let mut totals = Cost::new();
+      Ok(())
+    }
 
-        for i in 0..24 {
-          // generating 
-          totals += robots.iter().map(|r| r.produce()).sum();
+    #[test]
+    fn adventofcode2() -> Result<()> {
+      let blueprints = Day19::parse(&input::real(19)?)?;
+      assert_eq!(3472, Day19::part2(&blueprints));
 
-          //robots.push(Robots::Obsidian);
+      Ok(())
+    }
 
-          println!("{totals:?}");
+    // every order of building robots, each as soon as it is affordable, waiting out the end
+    fn brute_force(blueprint: &Blueprint, time_left: i32, robots: Cost, stock: Cost) -> i32 {
+      let mut best = stock.geode + robots.geode * time_left;
+      for robot in [Robots::Ore, Robots::Clay, Robots::Obsidian, Robots::Geode] {
+        let cost = blueprint.cost(&robot);
+        let mut stock = stock;
+        let mut time_left = time_left;
+        while time_left > 1 && !(cost.ore <= stock.ore && cost.clay <= stock.clay && cost.obsidian <= stock.obsidian) {
+          stock += robots;
+          time_left -= 1;
         }
+        if time_left > 1 && cost.ore <= stock.ore && cost.clay <= stock.clay && cost.obsidian <= stock.obsidian {
+          best = best.max(brute_force(blueprint, time_left - 1, robots + robot.produce(), stock + robots - *cost));
+        }
+      }
+
+      best
+    }
 
+    #[test]
+    fn generated_inputs() -> Result<()> {
+      // the full 24 minutes take too long to search blindly, 22 already give some geodes
+      let mut explored = Memo::bounded(EXPLORED_CAPACITY);
+      for seed in 0..10 {
+        for blueprint in Day19::parse(&crate::generate::input::<Day19>(seed, 2).text)? {
+          let geodes = brute_force(&blueprint, 22, Robots::Ore.produce(), Cost::new());
+          assert_eq!(geodes, blueprint.max_geodes(22, &mut explored), "seed {seed}: {blueprint:?}");
+        }
       }
+
+      Ok(())
     }
 }
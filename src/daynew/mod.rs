@@ -1,8 +0,0 @@
-#[cfg(test)]
-mod tests {
-
-  #[test]
-  fn adventofcode1() {
-    
-  }
-}
\ No newline at end of file
@@ -0,0 +1,24 @@
+use eyre::{eyre, Result};
+
+use crate::{
+    answers::Answers,
+    input::Source,
+    registry,
+    solution::Part,
+};
+
+fn check(day: u8, name: &str, part: Part) -> Result<()> {
+    let expected = Answers::load()?
+        .get(day, name, part)
+        .map(str::to_owned)
+        .ok_or_else(|| eyre!("no answer for day {day} {name} part {part} in {}", Answers::path().display()))?;
+
+    let solution = registry::find(day).ok_or_else(|| eyre!("unknown day {day}"))?;
+    let input = Source::Named(name.to_owned()).load(day)?;
+
+    assert_eq!(expected, solution.solve(&input, part)?);
+
+    Ok(())
+}
+
+include!(concat!(env!("OUT_DIR"), "/fixtures.rs"));
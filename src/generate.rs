@@ -0,0 +1,158 @@
+use std::ops::RangeInclusive;
+
+use eyre::{Result, WrapErr};
+
+use crate::{
+    input::{Params, Puzzle},
+    registry::Day,
+    runner,
+    solution::Solution,
+};
+
+pub const DEFAULT_SIZE: usize = 10;
+
+/// A small seeded generator, so a generated input can be made again from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix the seed so nearby seeds start far apart, xorshift can't start from 0
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        Rng((z ^ (z >> 31)).max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `0..n`, `n` being more than 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<isize>) -> isize {
+        let (start, end) = range.into_inner();
+
+        start + self.below((end - start) as usize + 1) as isize
+    }
+
+    /// True once every `n` times on average.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// `len` letters from `alphabet`.
+    pub fn word(&mut self, alphabet: &str, len: usize) -> String {
+        let letters: Vec<char> = alphabet.chars().collect();
+
+        (0..len).map(|_| *self.pick(&letters)).collect()
+    }
+}
+
+/// Days that can make up inputs of their own, valid and with an answer to both parts.
+pub trait Generate: Solution {
+    /// An input whose amount of lines, cells or steps grows with `size`.
+    fn generate(rng: &mut Rng, size: usize) -> String;
+
+    /// The parameters every generated input is read with.
+    fn params() -> Params {
+        Params::new()
+    }
+}
+
+pub fn input<S: Generate>(seed: u64, size: usize) -> Puzzle {
+    Puzzle { text: S::generate(&mut Rng::new(seed), size.max(1)), params: S::params() }
+}
+
+/// Runs `S` on the inputs of seeds `0..runs`, with sizes cycling through `sizes`, failing on the
+/// first one that doesn't parse, panics or whose answers don't satisfy `property`.
+///
+/// Failures give the seed and size, to run that input again with `generate`.
+pub fn check<S: Generate>(
+    runs: u64,
+    sizes: RangeInclusive<usize>,
+    mut property: impl FnMut(&str, &S::Input, &S::Answer1, &S::Answer2) -> Result<()>,
+) -> Result<()> {
+    let span = (sizes.end() - sizes.start() + 1) as u64;
+    for seed in 0..runs {
+        let size = sizes.start() + (seed % span) as usize;
+        let puzzle = input::<S>(seed, size);
+
+        runner::catch(|| {
+            let input = S::parse_with(&puzzle.text, &puzzle.params)?;
+            let (answer1, answer2) = (S::part1(&input), S::part2(&input));
+
+            property(&puzzle.text, &input, &answer1, &answer2)
+        })
+        .wrap_err_with(|| format!("generated input of seed {seed} and size {size}"))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the input of `day` made from `seed` and `size` to stdout, and the parameters to read it
+/// with to stderr.
+pub fn run(day: &Day, seed: u64, size: usize) -> Result<()> {
+    let puzzle = day.generate(seed, size);
+    print!("{}", puzzle.text);
+    if !puzzle.params.is_empty() {
+        eprintln!("read with the answers.json params {}", serde_json::to_string(&puzzle.params)?);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{registry, solution::Part};
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        let draws: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(draws, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Rng::new(8).next_u64(), draws[0]);
+
+        let mut rng = Rng::new(0);
+        assert!((0..1000).map(|_| rng.range(-3..=3)).all(|n| (-3..=3).contains(&n)));
+        let mut items: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!((0..20).collect::<Vec<_>>(), items);
+    }
+
+    #[test]
+    fn every_day_solves_its_generated_inputs() {
+        for day in registry::DAYS {
+            for seed in 0..3 {
+                let input = day.generate(seed, 3 + seed as usize);
+                let text = &input.text;
+                assert!(text.ends_with('\n') && !text.ends_with("\n\n"), "day {} seed {seed}: {text:?}", day.number);
+
+                for part in Part::ALL {
+                    let answer = runner::catch(|| day.solve(&input, part));
+                    assert!(answer.is_ok(), "day {} part {part} seed {seed}: {answer:?}\n{text}", day.number);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Index, IndexMut, Range},
+};
+
+use eyre::Result;
+
+use crate::{parse, point::Vec2};
+
+/// How a cell is drawn when its grid is displayed.
+pub trait CellChar {
+    fn char(&self) -> char;
+}
+
+impl CellChar for char {
+    fn char(&self) -> char {
+        *self
+    }
+}
+
+/// A dense rectangle of cells whose top left corner sits at `origin`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+    origin: Vec2,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid { cells: vec![fill; width * height], width, height, origin: Vec2::ZERO }
+    }
+
+    /// Cells given row by row, `width` to a row.
+    pub fn from_cells(cells: Vec<T>, width: usize) -> Self {
+        assert!(width > 0 && cells.len().is_multiple_of(width), "{} cells can't make rows of {width}", cells.len());
+        let height = cells.len() / width;
+
+        Grid { cells, width, height, origin: Vec2::ZERO }
+    }
+
+    /// Reads one cell per character, every row as wide as the first one.
+    pub fn parse(input: &str, mut cell: impl FnMut(char) -> Result<T>) -> Result<Self> {
+        let width = input.lines().next().unwrap_or_default().chars().count();
+        let rows = parse::each_line(input, |line| {
+            if line.chars().count() != width {
+                return Err(parse::expected(format!("{width} cells like the first row")));
+            }
+            line.chars().map(&mut cell).collect::<Result<Vec<_>>>()
+        })?;
+        let height = rows.len();
+
+        Ok(Grid { cells: rows.into_iter().flatten().collect(), width, height, origin: Vec2::ZERO })
+    }
+
+    /// Moves the grid so its top left cell is at `origin`.
+    pub fn with_origin(self, origin: Vec2) -> Self {
+        Grid { origin, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn x_range(&self) -> Range<isize> {
+        self.origin.x..self.origin.x + self.width as isize
+    }
+
+    pub fn y_range(&self) -> Range<isize> {
+        self.origin.y..self.origin.y + self.height as isize
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.x_range().contains(&pos.x) && self.y_range().contains(&pos.y)
+    }
+
+    fn index_of(&self, pos: Vec2) -> Option<usize> {
+        self.contains(pos).then(|| {
+            let offset = pos - self.origin;
+            offset.x as usize + offset.y as usize * self.width
+        })
+    }
+
+    pub fn get(&self, pos: Vec2) -> Option<&T> {
+        self.index_of(pos).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, pos: Vec2) -> Option<&mut T> {
+        self.index_of(pos).map(|index| &mut self.cells[index])
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.y_range().flat_map(move |y| self.x_range().map(move |x| Vec2::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &T)> + '_ {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter()
+    }
+
+    /// Neighbours of `pos` inside the grid, among the offsets in `deltas`.
+    pub fn neighbours<'a>(&'a self, pos: Vec2, deltas: &'a [Vec2]) -> impl Iterator<Item = Vec2> + 'a {
+        deltas
+            .iter()
+            .map(move |&delta| pos + delta)
+            .filter(|&pos| self.contains(pos))
+    }
+
+    pub fn neighbours4(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        self.neighbours(pos, &Vec2::NEIGHBOURS4)
+    }
+
+    pub fn neighbours8(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        self.neighbours(pos, &Vec2::NEIGHBOURS8)
+    }
+
+    /// Rows from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator + '_ {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Cells of row `y`, left to right; empty outside the grid.
+    pub fn row(&self, y: isize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let start = self.index_of(Vec2::new(self.origin.x, y)).unwrap_or(self.cells.len());
+        let end = if start < self.cells.len() { start + self.width } else { start };
+
+        self.cells[start..end].iter()
+    }
+
+    /// Cells of column `x`, top to bottom; empty outside the grid.
+    pub fn column(&self, x: isize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let start = self.index_of(Vec2::new(x, self.origin.y)).unwrap_or(self.cells.len());
+
+        self.cells[start..].iter().step_by(self.width.max(1))
+    }
+
+    /// Adds a row below the last one.
+    pub fn push_row(&mut self, row: impl IntoIterator<Item = T>) {
+        let before = self.cells.len();
+        self.cells.extend(row);
+        if self.height == 0 && self.width == 0 {
+            self.width = self.cells.len() - before;
+        }
+        assert_eq!(self.width, self.cells.len() - before, "rows of a grid must all be {} cells wide", self.width);
+        self.height += 1;
+    }
+
+    /// Draws every cell with `f`, one line per row.
+    pub fn render(&self, mut f: impl FnMut(Vec2, &T) -> char) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for (pos, cell) in self.iter() {
+            out.push(f(pos, cell));
+            if pos.x == self.x_range().end - 1 {
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+}
+
+impl<T> Index<Vec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Vec2) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Vec2> for Grid<T> {
+    fn index_mut(&mut self, pos: Vec2) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T: CellChar> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(|_, cell| cell.char()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse("abc\ndef\n", Ok).unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays() {
+        let grid = sample();
+
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&'f'), grid.get(Vec2::new(2, 1)));
+        assert_eq!(None, grid.get(Vec2::new(3, 1)));
+        assert_eq!("abc\ndef\n", grid.to_string());
+
+        let err = Grid::parse("abc\nde\n", Ok).unwrap_err();
+        assert_eq!("line 2: \"de\": expected 3 cells like the first row", format!("{err:#}"));
+    }
+
+    #[test]
+    fn rows_columns_and_neighbours() {
+        let grid = sample();
+
+        assert_eq!("def", grid.row(1).collect::<String>());
+        assert_eq!("be", grid.column(1).collect::<String>());
+        assert_eq!("", grid.row(2).collect::<String>());
+        assert_eq!(vec!["abc", "def"], grid.rows().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>());
+
+        assert_eq!(vec![Vec2::new(1, 0), Vec2::new(0, 1)], grid.neighbours4(Vec2::ZERO).collect::<Vec<_>>());
+        assert_eq!(5, grid.neighbours8(Vec2::new(1, 0)).count());
+    }
+
+    #[test]
+    fn offset_origin() {
+        let mut grid = sample().with_origin(Vec2::new(10, -1));
+
+        assert_eq!('a', grid[Vec2::new(10, -1)]);
+        assert_eq!(None, grid.get(Vec2::ZERO));
+        grid[Vec2::new(12, 0)] = 'z';
+        assert_eq!("dez", grid.row(0).collect::<String>());
+        assert_eq!("cz", grid.column(12).collect::<String>());
+
+        grid.push_row(['g', 'h', 'i']);
+        assert_eq!(Some(&'i'), grid.get(Vec2::new(12, 1)));
+        assert_eq!(vec![Vec2::new(10, -1), Vec2::new(11, -1)], grid.positions().take(2).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,274 @@
+use std::{fs, io, path::Path, process::Command, time::Duration};
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::bench::{DayReport, Stats};
+
+pub const DEFAULT_HISTORY: &str = "target/bench-history.json";
+/// How much slower, in percent, a median can get before `bench --compare` flags it.
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+// the latest runs shown by the trend charts
+const TREND_LENGTH: usize = 20;
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The `bench` runs at a git revision on one input, the latest run of each day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub revision: String,
+    pub input: String,
+    pub runs: usize,
+    pub days: Vec<DayReport>,
+}
+
+impl Entry {
+    fn stats(&self, day: u8, phase: &str) -> Option<Stats> {
+        let report = self.days.iter().find(|report| report.day == day)?;
+
+        report.timings.phases().into_iter().find(|&(name, _)| name == phase).map(|(_, stats)| stats)
+    }
+}
+
+/// Every `bench` run kept so far, oldest first, with at most one per revision and input.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub entries: Vec<Entry>,
+}
+
+impl History {
+    /// The history in `path`, empty when there is no such file yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(History::default()),
+            Err(e) => return Err(e).wrap_err_with(|| format!("reading {}", path.display())),
+        };
+
+        serde_json::from_str(&json).wrap_err_with(|| format!("reading {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json + "\n").wrap_err_with(|| format!("writing {}", path.display()))
+    }
+
+    /// Adds `entry` last, merged into an older one at the same revision and on the same input so
+    /// a run of only some days replaces just those.
+    pub fn record(&mut self, mut entry: Entry) {
+        let same = |old: &Entry| (&old.revision, &old.input) == (&entry.revision, &entry.input);
+        if let Some(index) = self.entries.iter().position(same) {
+            let old = self.entries.remove(index);
+            let rerun = |report: &DayReport| entry.days.iter().any(|new| new.day == report.day);
+            let kept: Vec<DayReport> = old.days.into_iter().filter(|report| !rerun(report)).collect();
+            entry.days.extend(kept);
+            entry.days.sort_by_key(|report| report.day);
+        }
+        self.entries.push(entry);
+    }
+
+    /// The latest run of `day` on the same input as `entry` but at another revision, to compare
+    /// it with.
+    pub fn baseline(&self, entry: &Entry, day: u8) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|old| old.input == entry.input && old.revision != entry.revision)
+            .find(|old| old.days.iter().any(|report| report.day == day))
+    }
+
+    /// Median times of `day`'s `phase` on `input`, oldest first.
+    pub fn medians(&self, input: &str, day: u8, phase: &str) -> Vec<u64> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.input == input)
+            .filter_map(|entry| entry.stats(day, phase))
+            .map(|stats| stats.median_ns)
+            .collect()
+    }
+}
+
+/// A phase of a day whose median got slower than the threshold allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub day: u8,
+    pub phase: &'static str,
+    pub before_ns: u64,
+    pub after_ns: u64,
+}
+
+impl Regression {
+    pub fn percent(&self) -> f64 {
+        change(self.before_ns, self.after_ns)
+    }
+}
+
+fn change(before_ns: u64, after_ns: u64) -> f64 {
+    (after_ns as f64 - before_ns as f64) / before_ns.max(1) as f64 * 100.0
+}
+
+/// Every phase of `current` more than `threshold` percent slower than in the baseline of its day.
+pub fn regressions(history: &History, current: &Entry, threshold: f64) -> Vec<Regression> {
+    current
+        .days
+        .iter()
+        .flat_map(|report| report.timings.phases().map(|(phase, stats)| (report.day, phase, stats)))
+        .filter_map(|(day, phase, stats)| {
+            let before_ns = history.baseline(current, day)?.stats(day, phase)?.median_ns;
+            let regression = Regression { day, phase, before_ns, after_ns: stats.median_ns };
+
+            (regression.percent() > threshold).then_some(regression)
+        })
+        .collect()
+}
+
+/// `values` as a row of bars, from the lowest to the highest.
+pub fn sparkline(values: &[u64]) -> String {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+
+    values
+        .iter()
+        .map(|&value| match max - min {
+            0 => BARS[BARS.len() / 2],
+            range => BARS[((value - min) as u128 * (BARS.len() - 1) as u128 / range as u128) as usize],
+        })
+        .collect()
+}
+
+/// The revision being benchmarked, `-dirty` when the working tree has uncommitted changes.
+pub fn revision() -> String {
+    let git = |args: &[&str]| Command::new("git").args(args).output().ok().filter(|output| output.status.success());
+
+    let Some(head) = git(&["rev-parse", "--short", "HEAD"]) else {
+        return "unknown".to_owned();
+    };
+    let head = String::from_utf8_lossy(&head.stdout).trim().to_owned();
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|status| !status.stdout.is_empty());
+
+    if dirty {
+        format!("{head}-dirty")
+    } else {
+        head
+    }
+}
+
+/// Prints a trend line per day and phase of `current`, then the ones that regressed since
+/// the latest other revision benchmarked on that day.
+pub fn compare(history: &History, current: &Entry, threshold: f64) -> Vec<Regression> {
+    let regressions = regressions(history, current, threshold);
+
+    println!("\ncompared on {}, threshold {threshold}%", current.input);
+    println!("day  phase  {:TREND_LENGTH$}  {:>12}  {:>8}  since", "trend", "median", "change");
+    for report in &current.days {
+        let baseline = history.baseline(current, report.day);
+        let since = baseline.map_or("-", |baseline| baseline.revision.as_str());
+        for (phase, stats) in report.timings.phases() {
+            let medians = history.medians(&current.input, report.day, phase);
+            let trend = sparkline(&medians[medians.len().saturating_sub(TREND_LENGTH)..]);
+            let change = baseline
+                .and_then(|baseline| baseline.stats(report.day, phase))
+                .map(|before| format!("{:+.1}%", change(before.median_ns, stats.median_ns)))
+                .unwrap_or_default();
+            let flag = if regressions.iter().any(|r| (r.day, r.phase) == (report.day, phase)) { "  REGRESSED" } else { "" };
+
+            println!(
+                "{:02}   {phase}  {trend:TREND_LENGTH$}  {:>12}  {change:>8}  {since}{flag}",
+                report.day,
+                format!("{:.2?}", Duration::from_nanos(stats.median_ns)),
+            );
+        }
+    }
+
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::Timings;
+
+    use super::*;
+
+    fn entry(revision: &str, input: &str, part2_ns: u64) -> Entry {
+        run(revision, input, &[(16, part2_ns)])
+    }
+
+    fn run(revision: &str, input: &str, days: &[(u8, u64)]) -> Entry {
+        let stats = |ns| Stats { min_ns: ns, median_ns: ns, max_ns: ns };
+        let days = days
+            .iter()
+            .map(|&(day, part2_ns)| DayReport { day, timings: Timings { parse: stats(100), part1: stats(1000), part2: stats(part2_ns) } })
+            .collect();
+
+        Entry { revision: revision.into(), input: input.into(), runs: 1, days }
+    }
+
+    #[test]
+    fn one_entry_per_revision_and_input() {
+        let mut history = History::default();
+        history.record(entry("a1", "real", 500));
+        history.record(entry("b2", "real", 600));
+        history.record(entry("b2", "example1", 50));
+        history.record(entry("b2", "real", 700));
+
+        let keys: Vec<(&str, &str)> = history.entries.iter().map(|e| (e.revision.as_str(), e.input.as_str())).collect();
+        assert_eq!(vec![("a1", "real"), ("b2", "example1"), ("b2", "real")], keys);
+        assert_eq!(vec![500, 700], history.medians("real", 16, "part2"));
+
+        let current = entry("c3-dirty", "real", 800);
+        assert_eq!("b2", history.baseline(&current, 16).unwrap().revision);
+        assert_eq!("a1", history.baseline(&entry("b2", "real", 0), 16).unwrap().revision);
+        assert!(history.baseline(&entry("c3", "example2", 0), 16).is_none());
+        assert!(history.baseline(&current, 17).is_none());
+    }
+
+    #[test]
+    fn slower_medians_beyond_the_threshold() {
+        let mut history = History::default();
+        history.record(entry("a1", "real", 1000));
+
+        assert!(regressions(&history, &entry("b2", "real", 1100), 10.0).is_empty());
+        let flagged = regressions(&history, &entry("b2", "real", 1101), 10.0);
+        assert_eq!(vec![Regression { day: 16, phase: "part2", before_ns: 1000, after_ns: 1101 }], flagged);
+        assert!((flagged[0].percent() - 10.1).abs() < 1e-9);
+        // faster is never flagged
+        assert!(regressions(&history, &entry("b2", "real", 10), 0.0).is_empty());
+    }
+
+    #[test]
+    fn partial_runs_keep_the_other_days() {
+        let mut history = History::default();
+        history.record(run("a1", "real", &[(16, 1000), (19, 2000)]));
+        history.record(run("b2", "real", &[(16, 1000), (19, 2000)]));
+        // only day 16 benchmarked again at the same revision
+        history.record(run("b2", "real", &[(16, 1500)]));
+
+        let b2 = history.entries.last().unwrap();
+        assert_eq!(vec![16, 19], b2.days.iter().map(|report| report.day).collect::<Vec<_>>());
+        assert_eq!(2, history.entries.len());
+        assert_eq!(vec![1000, 1500], history.medians("real", 16, "part2"));
+        assert_eq!(vec![2000, 2000], history.medians("real", 19, "part2"));
+
+        // then only day 19 at a new revision, compared with the latest run of day 19
+        history.record(run("c3", "real", &[(16, 900)]));
+        let current = run("c3", "real", &[(19, 3000)]);
+        history.record(current.clone());
+        assert_eq!("b2", history.baseline(&current, 19).unwrap().revision);
+        assert_eq!(
+            vec![Regression { day: 19, phase: "part2", before_ns: 2000, after_ns: 3000 }],
+            regressions(&history, &current, 10.0)
+        );
+    }
+
+    #[test]
+    fn sparklines() {
+        assert_eq!("▁▂▄█", sparkline(&[10, 20, 40, 80]));
+        assert_eq!("▅▅", sparkline(&[3, 3]));
+        assert_eq!("", sparkline(&[]));
+    }
+}
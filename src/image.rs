@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use eyre::{bail, eyre, Report, Result, WrapErr};
+
+use crate::{grid::Grid, input::Source, registry::Day, simulation::Simulation, solution::Part};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// `a` moved towards `b` by `t`, from 0 to 1.
+    pub fn mix(a: Rgb, b: Rgb, t: f64) -> Rgb {
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t.clamp(0.0, 1.0)).round() as u8;
+
+        Rgb(channel(a.0, b.0), channel(a.1, b.1), channel(a.2, b.2))
+    }
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// The colour of each character of a rendered frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+    fallback: Rgb,
+}
+
+impl Default for Palette {
+    // the characters the days draw with: open cells, rock, sand, walls, knots and markers
+    fn default() -> Self {
+        let background = Rgb(24, 24, 32);
+
+        Palette { colours: HashMap::new(), fallback: Rgb(255, 0, 255) }
+            .with(' ', background)
+            .with('.', background)
+            .with('#', Rgb(200, 200, 210))
+            .with('-', Rgb(110, 110, 120))
+            .with('|', Rgb(110, 110, 120))
+            .with('o', Rgb(230, 190, 90))
+            .with('~', Rgb(255, 230, 150))
+            .with('+', Rgb(255, 80, 80))
+            .with('@', Rgb(255, 130, 40))
+            .with('□', Rgb(80, 160, 255))
+            .with('H', Rgb(255, 80, 80))
+            .with_each("123456789", Rgb(255, 170, 170))
+            .with('s', Rgb(80, 220, 120))
+            .with('S', Rgb(80, 220, 120))
+            .with('E', Rgb(255, 80, 80))
+    }
+}
+
+impl Palette {
+    pub fn with(mut self, c: char, colour: Rgb) -> Self {
+        self.colours.insert(c, colour);
+        self
+    }
+
+    pub fn with_each(self, chars: &str, colour: Rgb) -> Self {
+        chars.chars().fold(self, |palette, c| palette.with(c, colour))
+    }
+
+    pub fn colour(&self, c: char) -> Rgb {
+        self.colours.get(&c).copied().unwrap_or(self.fallback)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageFormat {
+    // binary PPM, readable by most image tools
+    #[default]
+    Ppm,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "svg" => Ok(ImageFormat::Svg),
+            _ => Err(eyre!("unknown image format {s:?}, expected `ppm` or `svg`")),
+        }
+    }
+}
+
+/// A picture with one pixel per cell, scaled up when written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn from_grid<T>(grid: &Grid<T>, mut colour: impl FnMut(&T) -> Rgb) -> Self {
+        Image { width: grid.width(), height: grid.height(), pixels: grid.values().map(&mut colour).collect() }
+    }
+
+    /// One pixel per character, short lines padded with the colour of a space.
+    pub fn from_text(text: &str, palette: &Palette) -> Self {
+        let width = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let height = text.lines().count();
+        let mut pixels = vec![palette.colour(' '); width * height];
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                pixels[x + y * width] = palette.colour(c);
+            }
+        }
+
+        Image { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn row(&self, y: usize) -> &[Rgb] {
+        &self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Binary PPM with every pixel drawn as a `scale` by `scale` square.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+        for y in 0..self.height {
+            let row: Vec<u8> = self
+                .row(y)
+                .iter()
+                .flat_map(|&Rgb(r, g, b)| [r, g, b].repeat(scale))
+                .collect();
+            for _ in 0..scale {
+                out.extend_from_slice(&row);
+            }
+        }
+
+        out
+    }
+
+    /// SVG with a rectangle per run of same coloured pixels in a row.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+            self.width * scale,
+            self.height * scale,
+            self.width,
+            self.height,
+        );
+        for y in 0..self.height {
+            let row = self.row(y);
+            let mut x = 0;
+            while x < row.len() {
+                let run = row[x..].iter().take_while(|&&pixel| pixel == row[x]).count();
+                let _ = writeln!(out, "<rect x=\"{x}\" y=\"{y}\" width=\"{run}\" height=\"1\" fill=\"{}\"/>", row[x].hex());
+                x += run;
+            }
+        }
+        out.push_str("</svg>\n");
+
+        out
+    }
+
+    pub fn save(&self, path: &Path, format: ImageFormat, scale: usize) -> Result<()> {
+        let bytes = match format {
+            ImageFormat::Ppm => self.to_ppm(scale),
+            ImageFormat::Svg => self.to_svg(scale).into_bytes(),
+        };
+
+        fs::write(path, bytes).wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
+
+/// The steps an export of a single image runs before giving up on a simulation that never ends,
+/// such as the trillion rocks of day 17 part 2, unless given another limit.
+pub const DEFAULT_MAX_FRAMES: usize = 10_000_000;
+
+/// What `export` writes and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub format: ImageFormat,
+    pub scale: usize,
+    // the frame of a single image, the last one when not given
+    pub frame: Option<usize>,
+    // a sequence of every `every` frames instead of a single image
+    pub every: Option<usize>,
+    // the frame to stop at when the simulation is not done by then, needed by `every`
+    pub max_frames: Option<usize>,
+    pub output: Option<PathBuf>,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Export { format: ImageFormat::default(), scale: 4, frame: None, every: None, max_frames: None, output: None }
+    }
+}
+
+fn save_frame(simulation: &dyn Simulation, path: &Path, format: ImageFormat, scale: usize) -> Result<()> {
+    Image::from_text(&simulation.render(), &simulation.palette()).save(path, format, scale)
+}
+
+/// Writes every `every`-th frame of `simulation` and its last one into `dir`, as `frame-NNNNNN`
+/// numbered by step, returning how many were written. The last one is at most `max_frames`.
+pub fn frames(
+    simulation: &mut dyn Simulation,
+    dir: &Path,
+    every: usize,
+    max_frames: usize,
+    format: ImageFormat,
+    scale: usize,
+) -> Result<usize> {
+    fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+    let path = |step: usize| dir.join(format!("frame-{step:06}.{}", format.extension()));
+
+    let mut written = 0;
+    let mut step = 0;
+    loop {
+        let done = simulation.is_done() || step >= max_frames;
+        if step % every.max(1) == 0 || done {
+            save_frame(simulation, &path(step), format, scale)?;
+            written += 1;
+        }
+        if done {
+            return Ok(written);
+        }
+
+        simulation.step();
+        step += 1;
+    }
+}
+
+/// Writes images of `part` of `day` run on the input from `source`.
+///
+/// A sequence needs `max_frames`: without it a simulation that never ends would fill the disk
+/// before being found out.
+pub fn run(day: &Day, part: Part, source: &Source, export: &Export) -> Result<()> {
+    if let (Some(every), None) = (export.every, export.max_frames) {
+        bail!("exporting every {every} frames needs --max-frames, the frame to stop at");
+    }
+    let input = source.load(day.number)?;
+    let mut simulation = day.simulation(&input, part)?;
+    let name = format!("day{:02}-part{part}", day.number);
+    let limit = export.frame.or(export.max_frames);
+    let max_frames = limit.unwrap_or(DEFAULT_MAX_FRAMES);
+    if let Some(every) = export.every {
+        let dir = export.output.clone().unwrap_or_else(|| format!("{name}-frames").into());
+        let written = frames(simulation.as_mut(), &dir, every, max_frames, export.format, export.scale)?;
+        println!("{written} frames written to {}", dir.display());
+
+        return Ok(());
+    }
+
+    let mut step = 0;
+    while step < max_frames && !simulation.is_done() {
+        simulation.step();
+        step += 1;
+    }
+    // only an explicit limit may leave the simulation unfinished
+    if limit.is_none() && !simulation.is_done() {
+        bail!("day {} part {part} is not done after {max_frames} frames, give --frame or --max-frames", day.number);
+    }
+    let path = export.output.clone().unwrap_or_else(|| format!("{name}.{}", export.format.extension()).into());
+    save_frame(simulation.as_ref(), &path, export.format, export.scale)?;
+    println!("frame {step} written to {}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn ppm_and_svg() {
+        let palette = Palette::default().with('x', Rgb(1, 2, 3));
+        let image = Image::from_text("x#\n#\n", &palette);
+        assert_eq!((2, 2), (image.width(), image.height()));
+
+        let ppm = image.to_ppm(2);
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(header, &ppm[..header.len()]);
+        assert_eq!(header.len() + 4 * 4 * 3, ppm.len());
+        assert_eq!([1, 2, 3, 1, 2, 3, 200, 200, 210], ppm[header.len()..header.len() + 9]);
+
+        let svg = Image::from_text("..#\n", &palette).to_svg(10);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"10\" viewBox=\"0 0 3 1\""));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"2\" height=\"1\" fill=\"#181820\"/>\n<rect x=\"2\""));
+
+        let grid = Grid::parse("ab\n", Ok).unwrap();
+        let image = Image::from_grid(&grid, |&c| if c == 'a' { Rgb(0, 0, 0) } else { Rgb(255, 255, 255) });
+        assert_eq!(vec![Rgb(0, 0, 0), Rgb(255, 255, 255)], image.pixels);
+        assert_eq!(Rgb(128, 128, 128), Rgb::mix(Rgb(0, 0, 0), Rgb(255, 255, 255), 0.5));
+    }
+
+    struct Countdown(usize);
+
+    impl Simulation for Countdown {
+        fn step(&mut self) {
+            self.0 = self.0.saturating_sub(1);
+        }
+
+        fn is_done(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn render(&self) -> String {
+            format!("{}\n", "#".repeat(self.0))
+        }
+    }
+
+    #[test]
+    fn frame_sequences() -> Result<()> {
+        let dir = env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+
+        let written = frames(&mut Countdown(7), &dir, 3, 100, ImageFormat::Svg, 1)?;
+        let mut names = vec![];
+        for entry in fs::read_dir(&dir)? {
+            names.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(4, written);
+        assert_eq!(vec!["frame-000000.svg", "frame-000003.svg", "frame-000006.svg", "frame-000007.svg"], names);
+
+        // a simulation still running at `max_frames` stops there
+        let written = frames(&mut Countdown(1000), &dir, 2, 5, ImageFormat::Svg, 1)?;
+        let count = fs::read_dir(&dir)?.count();
+        fs::remove_dir_all(&dir)?;
+        assert_eq!((4, 4), (written, count));
+
+        Ok(())
+    }
+}
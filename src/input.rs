@@ -0,0 +1,167 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+
+use crate::answers::Answers;
+
+pub const INPUTS_DIR: &str = "inputs";
+
+/// Values a fixture sets next to its answers, for examples that ask something else than the real
+/// puzzle, such as the row of day 15.
+pub type Params = BTreeMap<String, isize>;
+
+/// The text of an input, with the parameters it is read with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Puzzle {
+    pub text: String,
+    pub params: Params,
+}
+
+impl From<&str> for Puzzle {
+    fn from(text: &str) -> Self {
+        Puzzle { text: text.to_owned(), params: Params::new() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    Stdin,
+    // a fixture of the day, e.g. `example1` for `inputs/dayNN/example1.txt`
+    Named(String),
+    File(PathBuf),
+}
+
+impl From<&str> for Source {
+    fn from(value: &str) -> Self {
+        match value {
+            "-" => Source::Stdin,
+            name if !name.contains(['/', '\\', '.']) => Source::Named(name.to_owned()),
+            path => Source::File(path.into()),
+        }
+    }
+}
+
+impl Source {
+    pub fn load(&self, day: u8) -> Result<Puzzle> {
+        let raw = match self {
+            Source::Default => read(&default_path(day))?,
+            Source::Stdin => {
+                let mut raw = String::new();
+                io::stdin().read_to_string(&mut raw).wrap_err("reading stdin")?;
+                raw
+            }
+            Source::Named(name) => read(&named_path(day, name))?,
+            Source::File(path) => read(path)?,
+        };
+
+        Ok(Puzzle { text: normalize(&raw), params: self.params(day)? })
+    }
+
+    // only fixtures have parameters, in answers.json
+    fn params(&self, day: u8) -> Result<Params> {
+        let name = match self {
+            Source::Default => "real",
+            Source::Named(name) => name,
+            Source::Stdin | Source::File(_) => return Ok(Params::new()),
+        };
+        if !Answers::path().exists() {
+            return Ok(Params::new());
+        }
+
+        Ok(Answers::load()?.params(day, name))
+    }
+
+    // how the input shows up in reports: a fixture name, a path or `-`
+    pub fn id(&self) -> String {
+        match self {
+            Source::Default => "real".to_owned(),
+            Source::Stdin => "-".to_owned(),
+            Source::Named(name) => name.clone(),
+            Source::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+pub fn day_dir(day: u8) -> PathBuf {
+    Path::new(INPUTS_DIR).join(format!("day{day:02}"))
+}
+
+pub fn named_path(day: u8, name: &str) -> PathBuf {
+    day_dir(day).join(format!("{name}.txt"))
+}
+
+pub fn default_path(day: u8) -> PathBuf {
+    named_path(day, "real")
+}
+
+/// An empty input file, left by `scaffold` to be filled in.
+pub fn is_placeholder(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.len() == 0)
+}
+
+// every `inputs/dayNN/*.txt` but placeholders, named after the file stem and sorted by name
+pub fn named_inputs(day: u8) -> Result<Vec<(String, PathBuf)>> {
+    let dir = day_dir(day);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e).wrap_err_with(|| format!("listing {}", dir.display())),
+    };
+
+    let mut inputs = vec![];
+    for entry in entries {
+        let path = entry.wrap_err_with(|| format!("listing {}", dir.display()))?.path();
+        if path.extension().is_some_and(|ext| ext == "txt") && !is_placeholder(&path) {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                inputs.push((name.to_owned(), path.clone()));
+            }
+        }
+    }
+    inputs.sort();
+
+    Ok(inputs)
+}
+
+#[cfg(test)]
+pub fn real(day: u8) -> Result<String> {
+    Ok(Source::Default.load(day)?.text)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))
+}
+
+// CRLF line endings and any number of trailing newlines become a single `\n`
+pub fn normalize(raw: &str) -> String {
+    let mut input = raw.replace("\r\n", "\n");
+    input.truncate(input.trim_end_matches('\n').len());
+    input.push('\n');
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_line_endings() {
+        assert_eq!("a\nb\n\nc\n", normalize("a\r\nb\r\n\r\nc"));
+        assert_eq!("a\n", normalize("a\n\n\n"));
+        assert_eq!("a\n", normalize("a\r\n\r\n"));
+        assert_eq!("\n", normalize(""));
+    }
+
+    #[test]
+    fn source_from_arg() {
+        assert_eq!(Source::Stdin, Source::from("-"));
+        assert_eq!(Source::Named("example1".into()), Source::from("example1"));
+        assert_eq!(Source::File("foo.txt".into()), Source::from("foo.txt"));
+        assert_eq!(Source::File("./example1".into()), Source::from("./example1"));
+    }
+}
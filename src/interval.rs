@@ -0,0 +1,264 @@
+use std::{fmt::Debug, ops::RangeInclusive};
+
+/// Integers an [`IntervalSet`] can hold.
+pub trait Integer: Copy + Ord + Debug {
+    fn to_i128(self) -> i128;
+    fn checked_succ(self) -> Option<Self>;
+    fn checked_pred(self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($t:ty),+) => {
+        $(
+            impl Integer for $t {
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn checked_succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn checked_pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+        )+
+    };
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// A set of integers stored as sorted, disjoint and non-adjacent inclusive ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // sorts and merges ranges that overlap or touch
+    fn normalised(mut ranges: Vec<(T, T)>) -> Self {
+        ranges.retain(|(start, end)| start <= end);
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some((_, last)) if last.checked_succ().is_none_or(|next| start <= next) => *last = end.max(*last),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> impl DoubleEndedIterator<Item = RangeInclusive<T>> + ExactSizeIterator + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> u128 {
+        self.ranges.iter().map(|(start, end)| (end.to_i128() - start.to_i128() + 1) as u128).sum()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(index).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Smallest range holding the whole set.
+    pub fn bounds(&self) -> Option<RangeInclusive<T>> {
+        Some(self.ranges.first()?.0..=self.ranges.last()?.1)
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range.into_inner());
+        *self = Self::normalised(ranges);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        let mut kept = Vec::with_capacity(self.ranges.len() + 1);
+        for &(a, b) in &self.ranges {
+            if b < start || a > end {
+                kept.push((a, b));
+                continue;
+            }
+            // a range sticking out on either side keeps that part
+            if let Some(before) = start.checked_pred().filter(|_| a < start) {
+                kept.push((a, before));
+            }
+            if let Some(after) = end.checked_succ().filter(|_| b > end) {
+                kept.push((after, b));
+            }
+        }
+        self.ranges = kept;
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalised(self.ranges.iter().chain(&other.ranges).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(&(a, b)), Some(&(c, d))) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (a.max(c), b.min(d));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // whichever ends first can't meet anything further in the other set
+            if b < d {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.ranges() {
+            result.remove(range);
+        }
+
+        result
+    }
+
+    /// Integers of `bounds` missing from the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        IntervalSet::from(bounds).difference(self)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+impl<T: Integer> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        Self::normalised(vec![range.into_inner()])
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        Self::normalised(iter.into_iter().map(RangeInclusive::into_inner).collect())
+    }
+}
+
+impl<T: Integer> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.extend(iter.into_iter().map(RangeInclusive::into_inner));
+        *self = Self::normalised(ranges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate::Rng;
+
+    use super::*;
+
+    // sets of 0..64 as a bitset, the obviously correct reference
+    fn random_set(rng: &mut Rng) -> (IntervalSet<u8>, u64) {
+        let mut set = IntervalSet::new();
+        let mut bits = 0u64;
+        for _ in 0..rng.below(5) {
+            let start = rng.below(64) as u8;
+            let end = (start as usize + rng.below(12)).min(63) as u8;
+            set.insert(start..=end);
+            bits |= (start..=end).fold(0, |acc, i| acc | 1 << i);
+        }
+
+        (set, bits)
+    }
+
+    fn bits_of(set: &IntervalSet<u8>) -> u64 {
+        (0..64).filter(|&i| set.contains(i)).fold(0, |acc, i| acc | 1 << i)
+    }
+
+    fn assert_normalised(set: &IntervalSet<u8>) {
+        assert!(set.ranges.iter().all(|(start, end)| start <= end), "{set:?}");
+        assert!(set.ranges.windows(2).all(|w| w[0].1 as u16 + 1 < w[1].0 as u16), "{set:?}");
+    }
+
+    #[test]
+    fn matches_a_bitset() {
+        let mut rng = Rng::new(0);
+
+        for _ in 0..2000 {
+            let (a, a_bits) = random_set(&mut rng);
+            let (b, b_bits) = random_set(&mut rng);
+            let low = rng.below(64) as u8;
+            let high = (low as usize + rng.below(64)).min(63) as u8;
+            let bounds_bits = (low..=high).fold(0u64, |acc, i| acc | 1 << i);
+
+            let results = [
+                (a.union(&b), a_bits | b_bits),
+                (a.intersection(&b), a_bits & b_bits),
+                (a.difference(&b), a_bits & !b_bits),
+                (a.complement(low..=high), bounds_bits & !a_bits),
+            ];
+            for (set, bits) in results {
+                assert_normalised(&set);
+                assert_eq!(bits, bits_of(&set), "{a:?} {b:?}");
+                assert_eq!(bits.count_ones() as u128, set.len());
+            }
+
+            assert_eq!(a_bits & !b_bits == 0, b.is_superset(&a));
+            assert_eq!(a_bits & b_bits != 0, a.overlaps(&b));
+
+            let mut removed = a.clone();
+            removed.remove(low..=high);
+            assert_normalised(&removed);
+            assert_eq!(a_bits & !bounds_bits, bits_of(&removed));
+        }
+    }
+
+    #[test]
+    fn edges_of_the_integer_range() {
+        let mut set = IntervalSet::from(0..=u8::MAX);
+        assert_eq!(256, set.len());
+
+        set.remove(0..=0);
+        set.remove(255..=255);
+        assert_eq!(vec![1..=254], set.ranges().collect::<Vec<_>>());
+        assert!(!set.contains(0) && set.contains(1) && !set.contains(255));
+
+        set.insert(255..=255);
+        set.insert(0..=0);
+        assert_eq!(IntervalSet::from(0..=u8::MAX), set);
+
+        let set: IntervalSet<isize> = [-5..=-1, 0..=3, 10..=12].into_iter().collect();
+        assert_eq!(vec![-5..=3, 10..=12], set.ranges().collect::<Vec<_>>());
+        assert_eq!(Some(-5..=12), set.bounds());
+        assert_eq!(vec![4..=9], set.complement(-5..=12).ranges().collect::<Vec<_>>());
+    }
+}
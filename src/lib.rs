@@ -0,0 +1,71 @@
+//! Advent of Code 2022 solutions and the tooling around them.
+//!
+//! Every `dayNN` module exposes a `DayNN` type implementing [`solution::Solution`] along with the
+//! types its puzzle is built on, such as [`day04::pairs::Pair`], [`day12::Grid`] or
+//! [`day13::Element`]. [`registry::DAYS`] lists them all for code that works on any day.
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+/// Playing simulations frame by frame in the terminal.
+pub mod animate;
+/// Expected answers recorded in `inputs/answers.json`.
+pub mod answers;
+/// Per-phase timings of the solutions.
+pub mod bench;
+/// Running every implementation of a part on the same inputs and reporting where they disagree.
+pub mod crosscheck;
+/// Finding where a simulation starts repeating, to skip ahead to any step.
+pub mod cycle;
+#[cfg(test)]
+mod fixtures;
+/// Random inputs for every day, to test the solutions on more than the puzzle inputs.
+pub mod generate;
+/// Dense 2D grids of cells.
+pub mod grid;
+/// Benchmark results kept by git revision, to catch regressions.
+pub mod history;
+/// Pictures of grids and simulation frames, as PPM or SVG.
+pub mod image;
+/// Where puzzle inputs come from and how they are read.
+pub mod input;
+/// Sets of integers kept as disjoint ranges.
+pub mod interval;
+/// Leveled diagnostics written to stderr.
+pub mod log;
+/// Caching the results of recursive searches.
+pub mod memo;
+/// Parser combinators and helpers that report the offending line and column.
+pub mod parse;
+/// 2D and 3D points and offsets.
+pub mod point;
+/// Every implemented day, looked up by number.
+pub mod registry;
+/// Solving days and parts, alone or all in parallel.
+pub mod runner;
+/// Generating the module and fixtures of a new day.
+pub mod scaffold;
+/// Breadth first, Dijkstra and A* searches over any graph.
+pub mod search;
+/// Puzzles played step by step, for the days that can be animated.
+pub mod simulation;
+/// The trait every day implements.
+pub mod solution;
+/// Checking answers against `inputs/answers.json`.
+pub mod verify;
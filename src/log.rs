@@ -0,0 +1,94 @@
+use std::{
+    fmt::{self, Display},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    // only answers and errors
+    #[default]
+    Quiet,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Quiet, Level::Info, Level::Debug, Level::Trace];
+
+    // `-v` is info, `-vv` debug and `-vvv` or more trace
+    pub fn from_verbosity(count: usize) -> Self {
+        Level::ALL[count.min(Level::ALL.len() - 1)]
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Quiet => write!(f, "quiet"),
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+            Level::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Quiet as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+// everything goes to stderr so stdout only carries answers
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log::enabled($level) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log::log!($crate::log::Level::Info, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log::log!($crate::log::Level::Debug, $($arg)*) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log::log!($crate::log::Level::Trace, $($arg)*) };
+}
+
+pub(crate) use {debug, info, log, trace};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity() {
+        assert_eq!(Level::Quiet, Level::from_verbosity(0));
+        assert_eq!(Level::Info, Level::from_verbosity(1));
+        assert_eq!(Level::Debug, Level::from_verbosity(2));
+        assert_eq!(Level::Trace, Level::from_verbosity(3));
+        assert_eq!(Level::Trace, Level::from_verbosity(7));
+    }
+
+    #[test]
+    fn levels_below_the_current_one_are_enabled() {
+        // the only test touching the global level
+        set_level(Level::Debug);
+        assert!(enabled(Level::Quiet));
+        assert!(enabled(Level::Info));
+        assert!(enabled(Level::Debug));
+        assert!(!enabled(Level::Trace));
+
+        set_level(Level::Quiet);
+        assert!(!enabled(Level::Info));
+    }
+}
@@ -1,23 +1,55 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-
-fn main(){
-
-}
\ No newline at end of file
+mod cli;
+
+use std::{env, panic};
+
+use adventofcode2022::{animate, bench, crosscheck, generate, image, log, registry, runner, scaffold, verify};
+use eyre::{eyre, Result};
+
+use cli::Command;
+
+fn main() -> Result<()> {
+    let (level, args) = cli::verbosity(env::args().skip(1));
+    log::set_level(level);
+
+    match Command::from_args(args)? {
+        Command::Run { day, part, source, format } => {
+            let day = registry::find(day).ok_or_else(|| eyre!("unknown day {day}"))?;
+
+            quiet_panics();
+            runner::run_one(day, part, &source, format)?;
+        }
+        Command::RunAll { source, format } => {
+            quiet_panics();
+            runner::run(&source, format)?;
+        }
+        Command::Verify => {
+            quiet_panics();
+            verify::run()?;
+        }
+        Command::Scaffold { day } => scaffold::run(day)?,
+        Command::Bench { day, options } => bench::run(day, &options)?,
+        Command::Animate { day, part, source, options } => {
+            animate::run(registry::find_animated(day)?, part, &source, options)?;
+        }
+        Command::Export { day, part, source, export } => {
+            image::run(registry::find_animated(day)?, part, &source, &export)?;
+        }
+        Command::Generate { day, seed, size } => {
+            let day = registry::find(day).ok_or_else(|| eyre!("unknown day {day}"))?;
+
+            generate::run(day, seed, size)?;
+        }
+        Command::Crosscheck { day, runs } => {
+            quiet_panics();
+            crosscheck::run(day, runs)?;
+        }
+    }
+
+    Ok(())
+}
+
+// these report the panics they catch as errors, the default hook would also print each with a
+// backtrace hint, in the middle of the table for those that have one
+fn quiet_panics() {
+    panic::set_hook(Box::new(|_| {}));
+}
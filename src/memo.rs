@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+/// How a [`Memo`] has been used since it was made or last cleared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits, {} misses, {} evicted", self.hits, self.misses, self.evictions)
+    }
+}
+
+/// Results of a recursive function by argument, so each one is only computed once.
+///
+/// A bounded memo forgets its oldest entries first, which only costs computing them again.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    // keys oldest first, only kept when bounded
+    order: VecDeque<K>,
+    capacity: Option<usize>,
+    stats: Stats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo { cache: HashMap::new(), order: VecDeque::new(), capacity: None, stats: Stats::default() }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps at most `capacity` results.
+    pub fn bounded(capacity: usize) -> Self {
+        Memo { capacity: Some(capacity.max(1)), ..Self::default() }
+    }
+
+    /// The result for `key`, computed by `f` when it isn't known yet; `f` gets the memo back to
+    /// recurse with.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+
+        let value = f(self);
+        self.insert(key, value.clone());
+
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        let Some(capacity) = self.capacity else {
+            self.cache.insert(key, value);
+            return;
+        };
+
+        if self.cache.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.cache.len() > capacity {
+            let Some(oldest) = self.order.pop_front() else { break };
+            self.cache.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Forgets every result and the stats, keeping the bound, to start on another input.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+        self.stats = Stats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+
+        memo.get_or_insert_with(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+    }
+
+    #[test]
+    fn computes_each_argument_once() {
+        let mut memo = Memo::new();
+        assert_eq!(12_586_269_025, fibonacci(&mut memo, 50));
+        assert_eq!(Stats { hits: 47, misses: 49, evictions: 0 }, memo.stats());
+        assert_eq!(49, memo.len());
+
+        assert_eq!(55, fibonacci(&mut memo, 10));
+        assert_eq!(48, memo.stats().hits);
+
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(Stats::default(), memo.stats());
+    }
+
+    #[test]
+    fn bounded_memos_forget_the_oldest_results() {
+        let mut memo = Memo::bounded(3);
+        for n in 0..5 {
+            memo.insert(n, n * 10);
+        }
+        memo.insert(4, 41);
+
+        assert_eq!(3, memo.len());
+        assert_eq!((None, Some(&20), Some(&41)), (memo.get(&1), memo.get(&2), memo.get(&4)));
+        assert_eq!(2, memo.stats().evictions);
+
+        // still right when it can't hold everything it is asked for
+        let mut memo = Memo::bounded(4);
+        assert_eq!(12_586_269_025, fibonacci(&mut memo, 50));
+        assert_eq!("memo: 47 hits, 49 misses, 45 evicted", format!("memo: {}", memo.stats()));
+    }
+}
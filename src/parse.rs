@@ -0,0 +1,279 @@
+use std::{fmt::Display, str::FromStr};
+
+use eyre::{eyre, Report, Result, WrapErr};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, one_of, space0},
+    combinator::{cut, eof, map_res, not, opt, recognize},
+    error::{ErrorKind, FromExternalError, ParseError},
+    multi::{many1, separated_list1},
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    IResult, Parser,
+};
+
+use crate::{grid::Grid, point::Vec2};
+
+pub fn expected(what: impl Display) -> Report {
+    eyre!("expected {what}")
+}
+
+// lines with their 1-based number, as editors show them
+pub fn numbered(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(index, line)| (index + 1, line))
+}
+
+pub fn at_line(number: usize, line: &str) -> String {
+    format!("line {number}: {line:?}")
+}
+
+pub fn expected_at(number: usize, line: &str, what: impl Display) -> Report {
+    expected(what).wrap_err(at_line(number, line))
+}
+
+// parses every line with `f`, failures say which line they come from
+pub fn each_line<T>(input: &str, mut f: impl FnMut(&str) -> Result<T>) -> Result<Vec<T>> {
+    numbered(input)
+        .map(|(number, line)| f(line).wrap_err_with(|| at_line(number, line)))
+        .collect()
+}
+
+pub fn number<T: FromStr>(text: &str, what: impl Display) -> Result<T> {
+    text.trim().parse().map_err(|_| expected(format!("{what}, found {text:?}")))
+}
+
+pub trait OrExpected<T> {
+    fn or_expected(self, what: impl Display) -> Result<T>;
+}
+
+impl<T> OrExpected<T> for Option<T> {
+    fn or_expected(self, what: impl Display) -> Result<T> {
+        self.ok_or_else(|| expected(what))
+    }
+}
+
+/// Where a combinator gave up, and what it wanted to find there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected<'a> {
+    rest: &'a str,
+    what: Option<String>,
+}
+
+impl<'a> ParseError<&'a str> for Expected<'a> {
+    fn from_error_kind(rest: &'a str, _: ErrorKind) -> Self {
+        Expected { rest, what: None }
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    // of two alternatives, the one that got further knows best what went wrong
+    fn or(self, other: Self) -> Self {
+        if other.rest.len() < self.rest.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for Expected<'a> {
+    fn from_external_error(rest: &'a str, _: ErrorKind, _: E) -> Self {
+        Expected { rest, what: None }
+    }
+}
+
+pub type Parsed<'a, T> = IResult<&'a str, T, Expected<'a>>;
+
+/// Says what `parser` expected when it fails without reading anything, errors further in are
+/// more precise and kept.
+pub fn named<'a, T>(what: impl Display, mut parser: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, T> {
+    let what = what.to_string();
+    move |input| {
+        parser.parse(input).map_err(|err| {
+            err.map(|err| match err.rest.len() == input.len() {
+                true => Expected { rest: input, what: Some(what.clone()) },
+                false => err,
+            })
+        })
+    }
+}
+
+/// Exactly `text`.
+pub fn literal<'a>(text: &'static str) -> impl FnMut(&'a str) -> Parsed<'a, &'a str> {
+    named(format!("`{text}`"), tag(text))
+}
+
+/// Integers with an optional sign, of any type they fit in.
+pub fn integer<'a, T: FromStr>(input: &'a str) -> Parsed<'a, T> {
+    named("an integer", map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse))(input)
+}
+
+/// `item`s separated by commas, with spaces after them or not.
+pub fn comma_list<'a, T>(item: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, Vec<T>> {
+    // once there is a comma, an item must follow
+    separated_list1(pair(char(','), space0), cut(item))
+}
+
+/// `key=value`, giving the value.
+pub fn key_value<'a, T>(key: &'static str, value: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, T> {
+    preceded(pair(literal(key), literal("=")), value)
+}
+
+/// `x=<x>, y=<y>`.
+pub fn vec2(input: &str) -> Parsed<'_, Vec2> {
+    let (rest, (x, y)) = separated_pair(key_value("x", integer), literal(", "), key_value("y", integer))(input)?;
+
+    Ok((rest, Vec2::new(x, y)))
+}
+
+// a line break with more of the block after it
+fn next_line(input: &str) -> Parsed<'_, &str> {
+    terminated(line_ending, not(alt((eof, line_ending))))(input)
+}
+
+/// Lines of `item`, one each, up to a blank line or the end.
+pub fn lines<'a, T>(item: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, Vec<T>> {
+    separated_list1(next_line, cut(item))
+}
+
+/// Groups of lines separated by a blank line, each read by `block`.
+pub fn blocks<'a, T>(block: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, Vec<T>> {
+    separated_list1(tuple((line_ending, line_ending, not(eof))), cut(block))
+}
+
+/// Rows of `cell`s up to a blank line or the end, every row as wide as the first one.
+pub fn grid<'a, T>(mut cell: impl Parser<&'a str, T, Expected<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, Grid<T>> {
+    move |input| {
+        let mut row = many1(|input| cell.parse(input));
+        let (mut input, mut cells) = row(input)?;
+        let width = cells.len();
+        while let Ok((next, _)) = next_line(input) {
+            let (rest, more) = row(next)?;
+            if more.len() != width {
+                return Err(nom::Err::Failure(Expected { rest: next, what: Some(format!("{width} cells like the first row")) }));
+            }
+            cells.extend(more);
+            input = rest;
+        }
+
+        Ok((input, Grid::from_cells(cells, width)))
+    }
+}
+
+/// Text like `move {} from {} to {}`, every `{}` a hole read by `hole`.
+pub fn template<'a, T, const N: usize>(
+    template: &'static str,
+    mut hole: impl Parser<&'a str, T, Expected<'a>>,
+) -> impl FnMut(&'a str) -> Parsed<'a, [T; N]> {
+    let pieces: Vec<&'static str> = template.split("{}").collect();
+    assert_eq!(N + 1, pieces.len(), "{template:?} should have {N} holes");
+
+    move |mut input| {
+        let mut holes = Vec::with_capacity(N);
+        for (index, piece) in pieces.iter().enumerate() {
+            (input, _) = literal(piece)(input)?;
+            if index < N {
+                let (rest, value) = hole.parse(input)?;
+                holes.push(value);
+                input = rest;
+            }
+        }
+        let Ok(holes) = holes.try_into() else { unreachable!() };
+
+        Ok((input, holes))
+    }
+}
+
+// the error of `parser` run on `input`, with the 1-based line and column it gave up at
+fn run<'a, T>(input: &'a str, end: &str, parser: impl Parser<&'a str, T, Expected<'a>>) -> Result<T, (usize, &'a str, String)> {
+    let mut whole = terminated(parser, pair(opt(line_ending), named(format!("the end of the {end}"), eof)));
+    let err = match whole(input) {
+        Ok((_, value)) => return Ok(value),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => err,
+        Err(nom::Err::Incomplete(_)) => Expected { rest: "", what: None },
+    };
+
+    let offset = input.len() - err.rest.len();
+    let start = input[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = input[start..].lines().next().unwrap_or_default();
+    let column = input[start..offset].chars().count() + 1;
+    let what = err.what.as_deref().unwrap_or("something else");
+
+    Err((input[..start].matches('\n').count() + 1, line, format!("{what} at column {column}")))
+}
+
+/// Runs `parser` on a single line, which it must read to the end.
+pub fn line<'a, T>(text: &'a str, parser: impl Parser<&'a str, T, Expected<'a>>) -> Result<T> {
+    run(text, "line", parser).map_err(|(_, _, what)| expected(what))
+}
+
+/// Runs `parser` on the whole input, failures say which line they come from.
+pub fn whole<'a, T>(input: &'a str, parser: impl Parser<&'a str, T, Expected<'a>>) -> Result<T> {
+    run(input, "input", parser).map_err(|(number, line, what)| expected_at(number, line, what))
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::character::complete::one_of;
+
+    use crate::{registry, runner, solution::Part};
+
+    use super::*;
+
+    #[test]
+    fn reports_line_and_text() {
+        let err = each_line("1\n2\nthree\n4\n", |line| number::<u32>(line, "a number")).unwrap_err();
+
+        assert_eq!("line 3: \"three\": expected a number, found \"three\"", format!("{err:#}"));
+        assert_eq!(vec![1, 2], each_line("1\n2\n", |line| number::<u32>(line, "a number")).unwrap());
+    }
+
+    #[test]
+    fn missing_values() {
+        let err = "a".split_once(',').or_expected("`<left>,<right>`").unwrap_err();
+
+        assert_eq!("expected `<left>,<right>`", err.to_string());
+    }
+
+    #[test]
+    fn combinators() {
+        assert_eq!(Ok(("", -12)), integer::<i32>("-12"));
+        assert_eq!(Ok(("", vec![1, 2, 3])), comma_list(integer::<u8>)("1, 2,3"));
+        assert_eq!(Ok(("", Vec2::new(2, -3))), vec2("x=2, y=-3"));
+        assert_eq!(Ok((" left", [4, 5])), template("move {} to {}", integer::<u8>)("move 4 to 5 left"));
+
+        let grid = whole("#.\n.#\n", grid(one_of(".#"))).unwrap();
+        assert_eq!((2, 2), (grid.width(), grid.height()));
+
+        let groups = whole("1\n2\n\n3\n", blocks(lines(integer::<u8>))).unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3]], groups);
+    }
+
+    #[test]
+    fn errors_tell_line_and_column() {
+        let err = whole("1, 2\n3, x\n", lines(comma_list(integer::<u8>))).unwrap_err();
+        assert_eq!("line 2: \"3, x\": expected an integer at column 4", format!("{err:#}"));
+
+        let err = whole("1\n2 3\n", lines(integer::<u8>)).unwrap_err();
+        assert_eq!("line 2: \"2 3\": expected the end of the input at column 2", format!("{err:#}"));
+
+        let err = whole("##\n#\n", grid(one_of(".#"))).unwrap_err();
+        assert_eq!("line 2: \"#\": expected 2 cells like the first row at column 1", format!("{err:#}"));
+
+        let err = line("move 4 from 5", template::<u8, 2>("move {} to {}", integer)).unwrap_err();
+        assert_eq!("expected ` to ` at column 7", err.to_string());
+    }
+
+    #[test]
+    fn every_day_rejects_garbage() {
+        for day in registry::DAYS {
+            let err = runner::catch(|| day.solve(&"x 1 y\n".into(), Part::One)).unwrap_err();
+            let message = format!("{err:#}");
+
+            assert!(message.starts_with("line 1: \"x 1 y\": expected"), "day {}: {message}", day.number);
+            assert!(!message.contains("panicked"), "day {}: {message}", day.number);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use eyre::{Report, Result};
+
+use crate::parse;
+
+/// A point or offset on a plane, `y` growing downwards as rows are read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec2 {
+    pub x: isize,
+    pub y: isize,
+}
+
+/// A point or offset in space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec3 {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2::new(0, 0);
+    pub const UP: Vec2 = Vec2::new(0, -1);
+    pub const DOWN: Vec2 = Vec2::new(0, 1);
+    pub const LEFT: Vec2 = Vec2::new(-1, 0);
+    pub const RIGHT: Vec2 = Vec2::new(1, 0);
+
+    /// Offsets to the cells sharing an edge, clockwise from up.
+    pub const NEIGHBOURS4: [Vec2; 4] = [Vec2::UP, Vec2::RIGHT, Vec2::DOWN, Vec2::LEFT];
+    /// Offsets to the cells sharing an edge or a corner, clockwise from up left.
+    pub const NEIGHBOURS8: [Vec2; 8] = [
+        Vec2::new(-1, -1),
+        Vec2::UP,
+        Vec2::new(1, -1),
+        Vec2::RIGHT,
+        Vec2::new(1, 1),
+        Vec2::DOWN,
+        Vec2::new(-1, 1),
+        Vec2::LEFT,
+    ];
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn manhattan(self, other: Vec2) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(self, other: Vec2) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// One step along each axis towards the sign of the component, or none.
+    pub fn signum(self) -> Vec2 {
+        Vec2::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn neighbours4(self) -> [Vec2; 4] {
+        Vec2::NEIGHBOURS4.map(|delta| self + delta)
+    }
+
+    pub fn neighbours8(self) -> [Vec2; 8] {
+        Vec2::NEIGHBOURS8.map(|delta| self + delta)
+    }
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0, 0, 0);
+
+    /// Offsets to the cubes sharing a face.
+    pub const NEIGHBOURS6: [Vec3; 6] = [
+        Vec3::new(1, 0, 0),
+        Vec3::new(-1, 0, 0),
+        Vec3::new(0, 1, 0),
+        Vec3::new(0, -1, 0),
+        Vec3::new(0, 0, 1),
+        Vec3::new(0, 0, -1),
+    ];
+
+    pub const fn new(x: isize, y: isize, z: isize) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Vec3) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev(self, other: Vec3) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y)).max(self.z.abs_diff(other.z))
+    }
+
+    pub fn signum(self) -> Vec3 {
+        Vec3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    pub fn neighbours6(self) -> [Vec3; 6] {
+        Vec3::NEIGHBOURS6.map(|delta| self + delta)
+    }
+}
+
+// component-wise operators, plus scaling by an integer
+macro_rules! ops {
+    ($vec:ident { $($field:ident),+ }) => {
+        impl Add for $vec {
+            type Output = $vec;
+
+            fn add(self, other: $vec) -> $vec {
+                $vec { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $vec {
+            type Output = $vec;
+
+            fn sub(self, other: $vec) -> $vec {
+                $vec { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl AddAssign for $vec {
+            fn add_assign(&mut self, other: $vec) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $vec {
+            fn sub_assign(&mut self, other: $vec) {
+                *self = *self - other;
+            }
+        }
+
+        impl Neg for $vec {
+            type Output = $vec;
+
+            fn neg(self) -> $vec {
+                $vec { $($field: -self.$field),+ }
+            }
+        }
+
+        impl Mul<isize> for $vec {
+            type Output = $vec;
+
+            fn mul(self, factor: isize) -> $vec {
+                $vec { $($field: self.$field * factor),+ }
+            }
+        }
+    };
+}
+
+ops!(Vec2 { x, y });
+ops!(Vec3 { x, y, z });
+
+impl From<(isize, isize)> for Vec2 {
+    fn from((x, y): (isize, isize)) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<(isize, isize, isize)> for Vec3 {
+    fn from((x, y, z): (isize, isize, isize)) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+// `1,2` or `x=1, y=2`, each component optionally prefixed by its name
+fn components<const N: usize>(text: &str, names: [&str; N], what: &str) -> Result<[isize; N]> {
+    let invalid = || parse::expected(format!("{what}, found {text:?}"));
+
+    let parts: Vec<&str> = text.split(',').map(str::trim).collect();
+    if parts.len() != N {
+        return Err(invalid());
+    }
+
+    let mut values = [0; N];
+    for ((value, part), name) in values.iter_mut().zip(parts).zip(names) {
+        let number = part.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')).unwrap_or(part);
+        *value = number.parse().map_err(|_| invalid())?;
+    }
+
+    Ok(values)
+}
+
+impl FromStr for Vec2 {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let [x, y] = components(s, ["x", "y"], "a point `<x>,<y>` or `x=<x>, y=<y>`")?;
+
+        Ok(Vec2::new(x, y))
+    }
+}
+
+impl FromStr for Vec3 {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let [x, y, z] = components(s, ["x", "y", "z"], "a point `<x>,<y>,<z>` or `x=<x>, y=<y>, z=<z>`")?;
+
+        Ok(Vec3::new(x, y, z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_distances() {
+        let a = Vec2::new(1, -2);
+        let b = Vec2::new(4, 2);
+
+        assert_eq!(Vec2::new(5, 0), a + b);
+        assert_eq!(Vec2::new(3, 4), b - a);
+        assert_eq!(Vec2::new(-2, 4), -a * 2);
+        assert_eq!(7, a.manhattan(b));
+        assert_eq!(4, a.chebyshev(b));
+        assert_eq!(Vec2::new(1, 1), (b - a).signum());
+
+        let mut c = Vec3::new(1, 2, 3);
+        c += Vec3::new(1, 1, 1);
+        c -= Vec3::new(0, 0, 5);
+        assert_eq!(Vec3::new(2, 3, -1), c);
+        assert_eq!(6, c.manhattan(Vec3::ZERO));
+        assert_eq!(3, c.chebyshev(Vec3::ZERO));
+    }
+
+    #[test]
+    fn neighbours() {
+        let p = Vec2::new(3, 3);
+
+        assert!(p.neighbours4().iter().all(|&n| n.manhattan(p) == 1));
+        assert!(p.neighbours8().iter().all(|&n| n.chebyshev(p) == 1));
+        assert_eq!(6, Vec3::ZERO.neighbours6().iter().filter(|n| n.manhattan(Vec3::ZERO) == 1).count());
+    }
+
+    #[test]
+    fn parsing() -> Result<()> {
+        assert_eq!(Vec2::new(498, 4), "498,4".parse()?);
+        assert_eq!(Vec2::new(-2, 15), "x=-2, y=15".parse()?);
+        assert_eq!(Vec3::new(2, 2, 2), "2,2,2".parse()?);
+        assert_eq!("1,2", Vec2::new(1, 2).to_string());
+
+        let err = "x=1".parse::<Vec2>().unwrap_err();
+        assert_eq!("expected a point `<x>,<y>` or `x=<x>, y=<y>`, found \"x=1\"", err.to_string());
+        assert!("1,2".parse::<Vec3>().is_err());
+        assert!("1,b".parse::<Vec2>().is_err());
+
+        Ok(())
+    }
+}
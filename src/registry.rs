@@ -0,0 +1,143 @@
+use eyre::{bail, eyre, Result};
+use itertools::Itertools;
+
+use crate::{
+    bench::{measure, Timings},
+    crosscheck::{self, Answers},
+    generate,
+    input::Puzzle,
+    simulation::{simulate, Simulation},
+    solution::{solve, Part},
+};
+
+type Simulate = fn(&Puzzle, Part) -> Result<Box<dyn Simulation>>;
+type Variants = fn(&Puzzle, Part) -> Result<Answers>;
+
+pub struct Day {
+    pub number: u8,
+    solve: fn(&Puzzle, Part) -> Result<String>,
+    bench: fn(&Puzzle, usize) -> Result<Timings>,
+    simulation: Option<Simulate>,
+    generate: fn(u64, usize) -> Puzzle,
+    variants: Option<Variants>,
+}
+
+impl Day {
+    pub fn solve(&self, input: &Puzzle, part: Part) -> Result<String> {
+        (self.solve)(input, part)
+    }
+
+    pub fn bench(&self, input: &Puzzle, runs: usize) -> Result<Timings> {
+        (self.bench)(input, runs)
+    }
+
+    /// A random input made from `seed`, growing with `size`.
+    pub fn generate(&self, seed: u64, size: usize) -> Puzzle {
+        (self.generate)(seed, size)
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.simulation.is_some()
+    }
+
+    pub fn simulation(&self, input: &Puzzle, part: Part) -> Result<Box<dyn Simulation>> {
+        let simulation = self.simulation.ok_or_else(|| eyre!("day {} has no animation", self.number))?;
+
+        simulation(input, part)
+    }
+
+    pub fn has_variants(&self) -> bool {
+        self.variants.is_some()
+    }
+
+    /// The answer of every implementation of `part`, or only of `part1` or `part2` for days with a
+    /// single one.
+    pub fn variants(&self, input: &Puzzle, part: Part) -> Result<Answers> {
+        match self.variants {
+            Some(variants) => variants(input, part),
+            None => Ok(vec![("default", self.solve(input, part))]),
+        }
+    }
+}
+
+// days followed by `(animated)` also implement `Animated`, by `(variants)` `Variants`
+macro_rules! days {
+    ($($number:literal => $module:ident::$solution:ident $(($($flag:ident),+))?),* $(,)?) => {
+        pub const DAYS: &[Day] = &[
+            $(Day {
+                number: $number,
+                solve: solve::<crate::$module::$solution>,
+                bench: measure::<crate::$module::$solution>,
+                simulation: days!(@simulation crate::$module::$solution; $($($flag)+)?),
+                generate: generate::input::<crate::$module::$solution>,
+                variants: days!(@variants crate::$module::$solution; $($($flag)+)?),
+            }),*
+        ];
+    };
+    (@simulation $solution:path;) => {
+        None
+    };
+    (@simulation $solution:path; animated $($flag:ident)*) => {
+        Some(simulate::<$solution>)
+    };
+    (@simulation $solution:path; $other:ident $($flag:ident)*) => {
+        days!(@simulation $solution; $($flag)*)
+    };
+    (@variants $solution:path;) => {
+        None
+    };
+    (@variants $solution:path; variants $($flag:ident)*) => {
+        Some(crosscheck::answers::<$solution>)
+    };
+    (@variants $solution:path; $other:ident $($flag:ident)*) => {
+        days!(@variants $solution; $($flag)*)
+    };
+}
+
+days! {
+    1 => day01::Day01 (variants),
+    2 => day02::Day02,
+    3 => day03::Day03,
+    4 => day04::Day04,
+    5 => day05::Day05,
+    6 => day06::Day06,
+    7 => day07::Day07,
+    8 => day08::Day08,
+    9 => day09::Day09 (animated),
+    10 => day10::Day10 (animated),
+    11 => day11::Day11,
+    12 => day12::Day12 (animated),
+    13 => day13::Day13,
+    14 => day14::Day14 (animated),
+    15 => day15::Day15 (variants),
+    16 => day16::Day16,
+    17 => day17::Day17 (animated),
+    18 => day18::Day18 (variants),
+    19 => day19::Day19,
+}
+
+pub fn find(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+/// The day `number`, when it can be animated or exported as images.
+pub fn find_animated(number: u8) -> Result<&'static Day> {
+    let day = find(number).ok_or_else(|| eyre!("unknown day {number}"))?;
+    if !day.is_animated() {
+        let animated = DAYS.iter().filter(|day| day.is_animated()).map(|day| day.number).join(", ");
+        bail!("day {number} has no animation, try one of {animated}");
+    }
+
+    Ok(day)
+}
+
+/// The day `number`, when it solves a part in more than one way.
+pub fn find_with_variants(number: u8) -> Result<&'static Day> {
+    let day = find(number).ok_or_else(|| eyre!("unknown day {number}"))?;
+    if !day.has_variants() {
+        let days = DAYS.iter().filter(|day| day.has_variants()).map(|day| day.number).join(", ");
+        bail!("day {number} has a single implementation of each part, try one of {days}");
+    }
+
+    Ok(day)
+}
@@ -0,0 +1,233 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use eyre::{bail, eyre, Report, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    input::Source,
+    log::info,
+    registry::{self, Day},
+    solution::Part,
+    verify::short,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    // one JSON object per line and per day and part
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(eyre!("unknown format {s:?}, expected `text` or `json`")),
+        }
+    }
+}
+
+pub struct Outcome {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub elapsed: Duration,
+    pub answer: Result<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub day: u8,
+    pub part: u8,
+    pub input: &'a str,
+    pub answer: Option<&'a str>,
+    pub elapsed_ns: u64,
+    pub error: Option<String>,
+}
+
+impl Outcome {
+    pub fn record(&self) -> Record<'_> {
+        Record {
+            day: self.day,
+            part: self.part.into(),
+            input: &self.input,
+            answer: self.answer.as_deref().ok(),
+            elapsed_ns: self.elapsed.as_nanos() as u64,
+            error: self.answer.as_ref().err().map(|e| format!("{e:#}")),
+        }
+    }
+
+    fn print_json(&self) -> Result<()> {
+        println!("{}", serde_json::to_string(&self.record())?);
+
+        Ok(())
+    }
+}
+
+// runs `f`, turning a panic into an error carrying the panic message
+pub fn catch<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_owned());
+
+        Err(eyre!("panicked: {message}"))
+    })
+}
+
+pub fn solve(day: &Day, source: &Source, part: Part) -> Outcome {
+    let start = Instant::now();
+    let answer = catch(|| day.solve(&source.load(day.number)?, part));
+    let elapsed = start.elapsed();
+    info!("day {:02} part {part} on {} took {elapsed:.2?}", day.number, source.id());
+
+    Outcome { day: day.number, part, input: source.id(), elapsed, answer }
+}
+
+pub fn run_one(day: &Day, part: Part, source: &Source, format: Format) -> Result<()> {
+    match format {
+        Format::Text => println!("{}", solve(day, source, part).answer?),
+        Format::Json => {
+            let outcome = solve(day, source, part);
+            outcome.print_json()?;
+            outcome.answer?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run_all(source: &Source) -> Vec<Outcome> {
+    let tasks: Vec<(&Day, Part)> = registry::DAYS
+        .iter()
+        .flat_map(|day| Part::ALL.map(|part| (day, part)))
+        .collect();
+
+    tasks.into_par_iter().map(|(day, part)| solve(day, source, part)).collect()
+}
+
+pub fn run(source: &Source, format: Format) -> Result<()> {
+    let start = Instant::now();
+    let outcomes = run_all(source);
+    let wall = start.elapsed();
+    let failed = outcomes.iter().filter(|outcome| outcome.answer.is_err()).count();
+
+    match format {
+        Format::Text => print_table(&outcomes, failed, wall),
+        Format::Json => {
+            for outcome in &outcomes {
+                outcome.print_json()?;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {} tasks failed", outcomes.len());
+    }
+
+    Ok(())
+}
+
+fn print_table(outcomes: &[Outcome], failed: usize, wall: Duration) {
+    let rows: Vec<[String; 5]> = outcomes
+        .iter()
+        .map(|outcome| {
+            let (status, detail) = match &outcome.answer {
+                Ok(answer) => ("ok", short(answer)),
+                Err(e) => ("FAILED", format!("{e:#}")),
+            };
+            [
+                format!("{:02}", outcome.day),
+                outcome.part.to_string(),
+                status.to_owned(),
+                format!("{:.2?}", outcome.elapsed),
+                detail,
+            ]
+        })
+        .collect();
+
+    let header = ["day", "part", "status", "time", "answer"].map(str::to_owned);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| rows.iter().chain([&header]).map(|row| row[column].chars().count()).max().unwrap_or_default())
+        .collect();
+
+    for row in [&header].into_iter().chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+
+    let total: Duration = outcomes.iter().map(|outcome| outcome.elapsed).sum();
+    println!();
+    println!(
+        "{} ok, {failed} failed in {wall:.2?} ({total:.2?} of work, rayon pool of {})",
+        outcomes.len() - failed,
+        rayon::current_num_threads(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_become_errors() {
+        let ok = catch(|| Ok(42));
+        assert_eq!(42, ok.unwrap());
+
+        let err = catch::<()>(|| panic!("bad input"));
+        assert_eq!("panicked: bad input", err.unwrap_err().to_string());
+
+        let err = catch::<()>(|| panic!("line {}", 3));
+        assert_eq!("panicked: line 3", err.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn every_day_and_part() {
+        let outcomes = run_all(&Source::Named("example1".into()));
+
+        assert_eq!(registry::DAYS.len() * 2, outcomes.len());
+        assert!(outcomes.iter().all(|outcome| outcome.answer.is_ok()));
+    }
+
+    #[test]
+    fn json_records() -> Result<()> {
+        let outcome = Outcome {
+            day: 4,
+            part: Part::Two,
+            input: "example1".into(),
+            elapsed: Duration::from_micros(3),
+            answer: Ok("42".into()),
+        };
+        assert_eq!(
+            r#"{"day":4,"part":2,"input":"example1","answer":"42","elapsed_ns":3000,"error":null}"#,
+            serde_json::to_string(&outcome.record())?
+        );
+
+        let outcome = Outcome { answer: Err(eyre!("bad input")), ..outcome };
+        assert_eq!(
+            r#"{"day":4,"part":2,"input":"example1","answer":null,"elapsed_ns":3000,"error":"bad input"}"#,
+            serde_json::to_string(&outcome.record())?
+        );
+
+        assert_eq!(Format::Json, "json".parse()?);
+        assert!("xml".parse::<Format>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{bail, eyre, Result, WrapErr};
+
+use crate::{answers::Answers, input};
+
+pub const SRC_DIR: &str = "src";
+
+const TEMPLATE: &str = include_str!("../templates/day.rs");
+
+pub fn run(day: u8) -> Result<()> {
+    let (module, inputs) = create(Path::new("."), day)?;
+
+    println!("created {}", module.display());
+    for name in FIXTURES {
+        println!("created an empty {}", inputs.join(format!("{name}.txt")).display());
+    }
+    println!("fill them in, with their answers in {}", Answers::path().display());
+
+    Ok(())
+}
+
+// the fixtures of a new day, empty until the puzzle is out
+const FIXTURES: [&str; 2] = ["example1", "real"];
+
+// the module of `day` in the crate at `root`, registered in lib.rs and registry.rs, with empty
+// fixtures and answers.json entries waiting for their answers
+fn create(root: &Path, day: u8) -> Result<(PathBuf, PathBuf)> {
+    if !(1..=25).contains(&day) {
+        bail!("day {day} is not between 1 and 25");
+    }
+
+    let src = root.join(SRC_DIR);
+    let module = src.join(format!("day{day:02}"));
+    if module.exists() {
+        bail!("{} already exists, refusing to overwrite day {day}", module.display());
+    }
+
+    let lib = src.join("lib.rs");
+    let registry = src.join("registry.rs");
+    let answers_path = root.join(Answers::path());
+    // edit every file in memory first so a failure leaves the tree untouched
+    let new_lib = register_module(&read(&lib)?, day)?;
+    let new_registry = register_day(&read(&registry)?, day)?;
+    let mut answers = Answers::read(&answers_path)?;
+    FIXTURES.iter().for_each(|name| answers.add(day, name));
+    let new_answers = answers.to_json()?;
+
+    create_dir(&module)?;
+    write(&module.join("mod.rs"), &render(day))?;
+
+    let inputs = root.join(input::day_dir(day));
+    create_dir(&inputs)?;
+    for name in FIXTURES {
+        write(&inputs.join(format!("{name}.txt")), "")?;
+    }
+
+    write(&lib, &new_lib)?;
+    write(&registry, &new_registry)?;
+    write(&answers_path, &new_answers)?;
+
+    Ok((module, inputs))
+}
+
+pub fn render(day: u8) -> String {
+    TEMPLATE
+        .replace("{{DAY}}", &format!("{day:02}"))
+        .replace("{{NUMBER}}", &day.to_string())
+}
+
+// `pub mod dayNN;` in lib.rs
+pub fn register_module(lib: &str, day: u8) -> Result<String> {
+    insert_sorted(lib, day, &format!("pub mod day{day:02};"), |line| {
+        line.strip_prefix("pub mod day")?.strip_suffix(';')?.parse().ok()
+    })
+}
+
+// `NN => dayNN::DayNN,` in the `days!` list of registry.rs
+pub fn register_day(registry: &str, day: u8) -> Result<String> {
+    insert_sorted(registry, day, &format!("{day} => day{day:02}::Day{day:02},"), |line| {
+        let (number, solution) = line.split_once(" => ")?;
+        solution.starts_with("day").then_some(())?;
+        number.parse().ok()
+    })
+}
+
+// inserts `entry` among the lines `day_of` recognises, keeping them sorted by day
+fn insert_sorted(source: &str, day: u8, entry: &str, day_of: impl Fn(&str) -> Option<u8>) -> Result<String> {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let days: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| Some((index, day_of(line.trim())?)))
+        .collect();
+
+    if days.iter().any(|&(_, existing)| existing == day) {
+        bail!("day {day} is already registered");
+    }
+    let &(last, _) = days.last().ok_or_else(|| eyre!("no registered day to insert day {day} next to"))?;
+
+    let index = days
+        .iter()
+        .find(|&&(_, existing)| existing > day)
+        .map_or(last + 1, |&(index, _)| index);
+    let neighbour = lines[index.min(last)];
+    let indent = &neighbour[..neighbour.len() - neighbour.trim_start().len()];
+
+    let line = format!("{indent}{entry}");
+    lines.insert(index, &line);
+
+    let mut result = lines.join("\n");
+    if source.ends_with('\n') {
+        result.push('\n');
+    }
+
+    Ok(result)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).wrap_err_with(|| format!("writing {}", path.display()))
+}
+
+fn create_dir(path: &Path) -> Result<()> {
+    fs::create_dir_all(path).wrap_err_with(|| format!("creating {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn creates_a_registered_day() -> Result<()> {
+        let root = env::temp_dir().join(format!("adventofcode2022-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        create_dir(&root.join(SRC_DIR))?;
+        create_dir(&root.join(input::INPUTS_DIR))?;
+        write(&root.join(SRC_DIR).join("lib.rs"), "pub mod day01;\npub mod input;\n")?;
+        write(&root.join(SRC_DIR).join("registry.rs"), "days! {\n    1 => day01::Day01,\n}\n")?;
+        write(&root.join(Answers::path()), "{\n  \"day01\": {\n    \"real\": {\n      \"part1\": \"1\"\n    }\n  }\n}\n")?;
+
+        let (module, inputs) = create(&root, 7)?;
+        assert_eq!(render(7), read(&module.join("mod.rs"))?);
+        assert_eq!("pub mod day01;\npub mod day07;\npub mod input;\n", read(&root.join(SRC_DIR).join("lib.rs"))?);
+        assert_eq!("days! {\n    1 => day01::Day01,\n    7 => day07::Day07,\n}\n", read(&root.join(SRC_DIR).join("registry.rs"))?);
+        for name in FIXTURES {
+            assert!(input::is_placeholder(&inputs.join(format!("{name}.txt"))), "{name}");
+        }
+        assert_eq!(
+            "{\n  \"day01\": {\n    \"real\": {\n      \"part1\": \"1\"\n    }\n  },\n  \"day07\": {\n    \"example1\": {},\n    \"real\": {}\n  }\n}\n",
+            read(&root.join(Answers::path()))?
+        );
+        assert!(create(&root, 7).is_err());
+
+        fs::remove_dir_all(&root)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn registers_module() -> Result<()> {
+        let lib = "pub mod day01;\npub mod day03;\npub mod input;\n";
+
+        assert_eq!("pub mod day01;\npub mod day02;\npub mod day03;\npub mod input;\n", register_module(lib, 2)?);
+        assert_eq!("pub mod day01;\npub mod day03;\npub mod day04;\npub mod input;\n", register_module(lib, 4)?);
+        assert!(register_module(lib, 3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn registers_day() -> Result<()> {
+        let registry = "days! {\n    1 => day01::Day01,\n    9 => day09::Day09,\n}\n";
+
+        assert_eq!(
+            "days! {\n    1 => day01::Day01,\n    9 => day09::Day09,\n    10 => day10::Day10,\n}\n",
+            register_day(registry, 10)?
+        );
+        assert_eq!(
+            "days! {\n    1 => day01::Day01,\n    2 => day02::Day02,\n    9 => day09::Day09,\n}\n",
+            register_day(registry, 2)?
+        );
+        assert!(register_day(registry, 9).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn renders_template() {
+        let module = render(7);
+
+        assert!(module.contains("pub struct Day07;"));
+        assert!(module.contains("impl Solution for Day07 {"));
+        assert!(module.contains("impl Generate for Day07 {"));
+        assert!(module.contains("todo!(\"day 7 part 1\")"));
+        assert!(!module.contains("{{"));
+    }
+}
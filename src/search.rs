@@ -0,0 +1,289 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Distances from the sources of a search, and the way back to them.
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    distances: HashMap<N, usize>,
+    previous: HashMap<N, N>,
+}
+
+/// A path found by a search, from a source to the goal, both included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N> {
+    pub cost: usize,
+    pub nodes: Vec<N>,
+}
+
+impl<N: Eq + Hash + Clone> Paths<N> {
+    fn new() -> Self {
+        Paths { distances: HashMap::new(), previous: HashMap::new() }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<usize> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.distances.contains_key(node)
+    }
+
+    /// Every node reached, with its distance.
+    pub fn distances(&self) -> &HashMap<N, usize> {
+        &self.distances
+    }
+
+    /// Nodes from the closest source to `node`, both included.
+    pub fn path_to(&self, node: &N) -> Option<Path<N>> {
+        let cost = self.distance(node)?;
+        let mut nodes = vec![node.clone()];
+        while let Some(previous) = self.previous.get(nodes.last().unwrap()) {
+            nodes.push(previous.clone());
+        }
+        nodes.reverse();
+
+        Some(Path { cost, nodes })
+    }
+}
+
+// stops at the first node `goal` accepts, or once everything reachable is seen
+fn breadth_first<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Paths<N>, Option<N>)
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if paths.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            return (paths, Some(node));
+        }
+
+        let distance = paths.distances[&node] + 1;
+        for next in neighbours(&node) {
+            if let Entry::Vacant(entry) = paths.distances.entry(next.clone()) {
+                entry.insert(distance);
+                paths.previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (paths, None)
+}
+
+// Dijkstra, or A* when `heuristic` is not always zero
+fn best_first<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Paths<N>, Option<N>)
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut paths = Paths::new();
+    // the heap refers to nodes by index so they need not be `Ord`
+    let mut nodes = vec![];
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if paths.distances.insert(start.clone(), 0).is_none() {
+            heap.push(Reverse((heuristic(&start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, cost, index))) = heap.pop() {
+        let node = nodes[index].clone();
+        if paths.distances[&node] < cost {
+            // a shorter way here was found after this entry was queued
+            continue;
+        }
+        if goal(&node) {
+            return (paths, Some(node));
+        }
+
+        for (next, weight) in neighbours(&node) {
+            let next_cost = cost + weight;
+            if paths.distance(&next).is_some_and(|known| known <= next_cost) {
+                continue;
+            }
+            paths.distances.insert(next.clone(), next_cost);
+            paths.previous.insert(next.clone(), node.clone());
+            heap.push(Reverse((next_cost + heuristic(&next), next_cost, nodes.len())));
+            nodes.push(next);
+        }
+    }
+
+    (paths, None)
+}
+
+fn found<N: Eq + Hash + Clone>((paths, goal): (Paths<N>, Option<N>)) -> Option<Path<N>> {
+    paths.path_to(&goal?)
+}
+
+/// Explores everything reachable from `starts`, one step per edge.
+pub fn bfs<N, I>(starts: impl IntoIterator<Item = N>, neighbours: impl FnMut(&N) -> I) -> Paths<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    breadth_first(starts, neighbours, |_| false).0
+}
+
+/// Shortest path from any of `starts` to the first node `goal` accepts, one step per edge.
+pub fn bfs_to<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    found(breadth_first(starts, neighbours, goal))
+}
+
+/// Explores everything reachable from `starts`, `neighbours` giving the cost of each edge.
+pub fn dijkstra<N, I>(starts: impl IntoIterator<Item = N>, neighbours: impl FnMut(&N) -> I) -> Paths<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    best_first(starts, neighbours, |_| 0, |_| false).0
+}
+
+/// Cheapest path from any of `starts` to the first node `goal` accepts.
+pub fn dijkstra_to<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    found(best_first(starts, neighbours, |_| 0, goal))
+}
+
+/// Like [`dijkstra_to`], guided by a `heuristic` that never overestimates the cost left.
+pub fn astar<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> usize,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    found(best_first(starts, neighbours, heuristic, goal))
+}
+
+/// Cost of the cheapest path between every pair of nodes reachable from `nodes`.
+pub fn all_pairs<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> HashMap<N, HashMap<N, usize>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    nodes
+        .into_iter()
+        .map(|node| {
+            let paths = dijkstra([node.clone()], &mut neighbours);
+            (node, paths.distances)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a - b - c
+    // |       |
+    // d ----- e (the d-e edge costs 5)
+    fn edges(node: &char) -> Vec<(char, usize)> {
+        match node {
+            'a' => vec![('b', 1), ('d', 1)],
+            'b' => vec![('a', 1), ('c', 1)],
+            'c' => vec![('b', 1), ('e', 1)],
+            'd' => vec![('a', 1), ('e', 5)],
+            'e' => vec![('c', 1), ('d', 5)],
+            _ => vec![],
+        }
+    }
+
+    fn steps(node: &char) -> Vec<char> {
+        edges(node).into_iter().map(|(next, _)| next).collect()
+    }
+
+    #[test]
+    fn breadth_first_counts_edges() {
+        let paths = bfs(['a'], steps);
+        assert_eq!(Some(2), paths.distance(&'e'));
+        assert_eq!(5, paths.distances().len());
+        assert!(!paths.contains(&'z'));
+
+        let path = bfs_to(['a'], steps, |&node| node == 'e').unwrap();
+        assert_eq!(2, path.cost);
+        assert_eq!(vec!['a', 'd', 'e'], path.nodes);
+
+        // the closest of several sources wins
+        let path = bfs_to(['a', 'c'], steps, |&node| node == 'e').unwrap();
+        assert_eq!(vec!['c', 'e'], path.nodes);
+        assert_eq!(None, bfs_to(['a'], steps, |&node| node == 'z'));
+    }
+
+    #[test]
+    fn weighted_searches_follow_the_cheapest_edges() {
+        let path = dijkstra_to(['a'], edges, |&node| node == 'e').unwrap();
+        assert_eq!(Path { cost: 3, nodes: vec!['a', 'b', 'c', 'e'] }, path);
+        assert_eq!(Some(3), dijkstra(['d'], edges).distance(&'c'));
+
+        let path = astar(['a'], edges, |&node| if node == 'e' { 0 } else { 1 }, |&node| node == 'e');
+        assert_eq!(Some(3), path.map(|path| path.cost));
+    }
+
+    #[test]
+    fn every_pair() {
+        let costs = all_pairs("abcde".chars(), edges);
+
+        assert_eq!(3, costs[&'a'][&'e']);
+        assert_eq!(3, costs[&'e'][&'a']);
+        assert_eq!(0, costs[&'c'][&'c']);
+    }
+
+    #[test]
+    fn astar_on_a_grid() {
+        use crate::point::Vec2;
+
+        // an open 50x50 square with a wall in the middle
+        let open = |p: Vec2| (0..50).contains(&p.x) && (0..50).contains(&p.y) && !(p.x == 25 && p.y < 40);
+        let neighbours = |p: &Vec2| p.neighbours4().into_iter().filter(|&n| open(n)).map(|n| (n, 1));
+        let goal = Vec2::new(49, 0);
+
+        let guided = astar([Vec2::ZERO], neighbours, |p| p.manhattan(goal), |&p| p == goal).unwrap();
+        let plain = dijkstra_to([Vec2::ZERO], neighbours, |&p| p == goal).unwrap();
+
+        assert_eq!(plain.cost, guided.cost);
+        assert_eq!(guided.cost + 1, guided.nodes.len());
+        assert!(guided.nodes.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+    }
+}
@@ -0,0 +1,44 @@
+use eyre::Result;
+
+use crate::{
+    image::Palette,
+    input::Puzzle,
+    solution::{Part, Solution},
+};
+
+/// A puzzle played one step at a time, drawable between any two steps.
+pub trait Simulation {
+    /// Moves on by one step, doing nothing once done.
+    fn step(&mut self);
+
+    fn is_done(&self) -> bool;
+
+    /// The current state, one line per row.
+    fn render(&self) -> String;
+
+    /// A line about the current state, shown under it when animated.
+    fn status(&self) -> String {
+        String::new()
+    }
+
+    /// Colours of the characters [`Simulation::render`] draws with, for images.
+    fn palette(&self) -> Palette {
+        Palette::default()
+    }
+
+    /// Steps until done.
+    fn run(&mut self) {
+        while !self.is_done() {
+            self.step();
+        }
+    }
+}
+
+/// Days whose solution can be watched step by step.
+pub trait Animated: Solution {
+    fn simulation(input: Self::Input, part: Part) -> Box<dyn Simulation>;
+}
+
+pub fn simulate<S: Animated>(puzzle: &Puzzle, part: Part) -> Result<Box<dyn Simulation>> {
+    Ok(S::simulation(S::parse_with(&puzzle.text, &puzzle.params)?, part))
+}
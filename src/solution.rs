@@ -0,0 +1,99 @@
+use std::fmt::{self, Display};
+
+use eyre::{bail, eyre, Result};
+
+use crate::input::{Params, Puzzle};
+
+/// What a part prints, or why that input has no answer to it.
+pub trait Answer {
+    fn answer(self) -> Result<String>;
+}
+
+macro_rules! answers {
+    ($($answer:ty),*) => {
+        $(impl Answer for $answer {
+            fn answer(self) -> Result<String> {
+                Ok(self.to_string())
+            }
+        })*
+    };
+}
+
+answers!(i32, u64, u128, usize, isize, String);
+
+// for parts whose requirements the other part doesn't share
+impl<T: Answer> Answer for Result<T> {
+    fn answer(self) -> Result<String> {
+        self?.answer()
+    }
+}
+
+pub trait Solution {
+    type Input;
+    type Answer1: Answer;
+    type Answer2: Answer;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    /// Reads the input with the parameters of its fixture, which most days take none of.
+    fn parse_with(input: &str, params: &Params) -> Result<Self::Input> {
+        if let Some(name) = params.keys().next() {
+            bail!("unknown parameter {name:?}");
+        }
+
+        Self::parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1;
+
+    fn part2(input: &Self::Input) -> Self::Answer2;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl TryFrom<u8> for Part {
+    type Error = eyre::Report;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(eyre!("unknown part {value}")),
+        }
+    }
+}
+
+impl From<Part> for u8 {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+pub fn solve<S: Solution>(puzzle: &Puzzle, part: Part) -> Result<String> {
+    let input = S::parse_with(&puzzle.text, &puzzle.params)?;
+
+    match part {
+        Part::One => S::part1(&input).answer(),
+        Part::Two => S::part2(&input).answer(),
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::{self, Display};
+
+use eyre::{bail, eyre, Result};
+
+use crate::{
+    answers::Answers,
+    input::{self, Source},
+    registry,
+    runner,
+    solution::Part,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail { expected: String },
+    Missing,
+    Error(String),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "PASS"),
+            Status::Fail { .. } => write!(f, "FAIL"),
+            Status::Missing => write!(f, "MISSING"),
+            Status::Error(_) => write!(f, "ERROR"),
+        }
+    }
+}
+
+pub struct Check {
+    pub day: u8,
+    pub input: String,
+    pub part: Part,
+    pub answer: Option<String>,
+    pub status: Status,
+}
+
+pub fn check(answer: Result<String>, expected: Option<&str>) -> (Option<String>, Status) {
+    match (answer, expected) {
+        (Err(e), _) => (None, Status::Error(format!("{e:#}"))),
+        (Ok(answer), None) => (Some(answer), Status::Missing),
+        (Ok(answer), Some(expected)) if answer == expected => (Some(answer), Status::Pass),
+        (Ok(answer), Some(expected)) => (Some(answer), Status::Fail { expected: expected.to_owned() }),
+    }
+}
+
+pub fn check_all(answers: &Answers) -> Result<Vec<Check>> {
+    let mut checks = vec![];
+
+    for day in registry::DAYS {
+        for (name, _) in input::named_inputs(day.number)? {
+            let input = Source::Named(name.clone()).load(day.number);
+
+            for part in Part::ALL {
+                let answer = match &input {
+                    Ok(input) => runner::catch(|| day.solve(input, part)),
+                    Err(e) => Err(eyre!("{e:#}")),
+                };
+                let (answer, status) = check(answer, answers.get(day.number, &name, part));
+
+                checks.push(Check { day: day.number, input: name.clone(), part, answer, status });
+            }
+        }
+    }
+
+    Ok(checks)
+}
+
+// multi-line answers (day 10's CRT) only show their first line in the table
+pub fn short(answer: &str) -> String {
+    match answer.split_once('\n') {
+        Some((first, _)) => format!("{first} …"),
+        None => answer.to_owned(),
+    }
+}
+
+pub fn run() -> Result<()> {
+    let answers = Answers::load()?;
+    let checks = check_all(&answers)?;
+
+    let input_width = checks.iter().map(|c| c.input.len()).chain([5]).max().unwrap_or_default();
+
+    println!("day  part  {:input_width$}  status   answer", "input");
+    for check in &checks {
+        let answer = check.answer.as_deref().map(short).unwrap_or_default();
+        let detail = match &check.status {
+            Status::Fail { expected } => format!("{answer} (expected {})", short(expected)),
+            Status::Error(e) => e.clone(),
+            _ => answer,
+        };
+
+        println!(
+            "{:02}   {}     {:input_width$}  {:7}  {detail}",
+            check.day,
+            check.part,
+            check.input,
+            check.status.to_string(),
+        );
+    }
+
+    let count = |f: fn(&Status) -> bool| checks.iter().filter(|c| f(&c.status)).count();
+    let passed = count(|s| *s == Status::Pass);
+    let failed = count(|s| matches!(s, Status::Fail { .. } | Status::Error(_)));
+    let missing = count(|s| *s == Status::Missing);
+
+    println!();
+    println!("{passed} passed, {failed} failed, {missing} missing from {}", Answers::path().display());
+
+    if failed > 0 {
+        bail!("{failed} check(s) failed");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses() {
+        assert_eq!(Status::Pass, check(Ok("42".into()), Some("42")).1);
+        assert_eq!(Status::Fail { expected: "41".into() }, check(Ok("42".into()), Some("41")).1);
+        assert_eq!(Status::Missing, check(Ok("42".into()), None).1);
+        assert_eq!(Status::Error("bad input".into()), check(Err(eyre!("bad input")), Some("42")).1);
+    }
+
+    #[test]
+    fn multi_line_answer() {
+        assert_eq!("#..# …", short("#..#\n.##."));
+        assert_eq!("42", short("42"));
+    }
+}
@@ -0,0 +1,34 @@
+//! Day {{NUMBER}}.
+
+use eyre::Result;
+
+use crate::{
+    generate::{Generate, Rng},
+    solution::Solution,
+};
+
+pub struct Day{{DAY}};
+
+impl Solution for Day{{DAY}} {
+    type Input = Vec<String>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(str::to_owned).collect())
+    }
+
+    fn part1(_input: &Self::Input) -> usize {
+        todo!("day {{NUMBER}} part 1")
+    }
+
+    fn part2(_input: &Self::Input) -> usize {
+        todo!("day {{NUMBER}} part 2")
+    }
+}
+
+impl Generate for Day{{DAY}} {
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| format!("{}\n", rng.below(100))).collect()
+    }
+}
@@ -0,0 +1,44 @@
+use adventofcode2022::{
+    day04::pairs::Pair,
+    day12::Grid,
+    day13::Element,
+    day15::Sensor,
+    interval::IntervalSet,
+    point::Vec2,
+    registry,
+    solution::{self, Part},
+};
+use eyre::Result;
+
+#[test]
+fn day_types_are_public() -> Result<()> {
+    let pair: Pair = "2-8,3-7".parse()?;
+    assert!(pair.overlap());
+
+    let grid = Grid::new("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi")?;
+    assert_eq!(Some(31), grid.steps_from_start());
+    assert_eq!(Some(29), grid.steps_from_lowest());
+
+    let left: Element = "[[1],[2,3,4]]".parse()?;
+    let right: Element = "[[1],4]".parse()?;
+    assert!(left < right);
+
+    let sensor = Sensor::new(Vec2::new(8, 7), Vec2::new(2, 10));
+    let mut free = IntervalSet::from(0..=20);
+    free.remove(sensor.x_range_at(10).unwrap());
+    assert_eq!(vec![0..=1, 15..=20], free.ranges().collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn days_solve_through_the_registry() -> Result<()> {
+    let day = registry::find(1).unwrap();
+    let example = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+    assert_eq!("24000", day.solve(&example.into(), Part::One)?);
+
+    let answer = solution::solve::<adventofcode2022::day01::Day01>(&"1\n\n2\n".into(), Part::Two)?;
+    assert_eq!("3", answer);
+
+    Ok(())
+}